@@ -0,0 +1,199 @@
+use alloc::boxed::Box;
+
+use crate::{command::{helpers, parse::ParsedCommand, section_offsets}, CommandType};
+
+/// A serialized command with its shape erased from the type
+///
+/// Every [`HipcCommandBuilder`](crate::command::HipcCommandBuilder) has a distinct type, so
+/// commands of different shapes cannot be stored together. A boxed command holds the
+/// serialized bytes alongside the section counts the builder knew at compile time, so they
+/// can be collected at runtime.
+///
+/// Construct one with [`HipcCommandBuilder::into_boxed`](crate::command::HipcCommandBuilder::into_boxed).
+///
+/// # Example
+/// ```
+/// use hipc::{boxed::BoxedCommand, command, packed::BufferDescriptor};
+///
+/// let commands: Vec<BoxedCommand> = vec![
+///     command::request_builder()
+///         .with_raw_data([1u32, 2])
+///         .into_boxed(),
+///     command::control_builder()
+///         .with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+///         .into_boxed()
+/// ];
+///
+/// assert_eq!(commands[0].raw_data_words(), 2);
+/// assert_eq!(commands[0].as_bytes().len(), 16);
+/// assert_eq!(commands[1].num_send_buffers(), 1);
+/// assert_eq!(commands[1].as_bytes().len(), 20);
+/// ```
+#[derive(Clone, Debug)]
+pub struct BoxedCommand {
+    pub(crate) ty: CommandType,
+    pub(crate) send_statics: usize,
+    pub(crate) send_buffers: usize,
+    pub(crate) recv_buffers: usize,
+    pub(crate) exch_buffers: usize,
+    pub(crate) recv_statics: usize,
+    pub(crate) special_header_bytes: usize,
+    pub(crate) has_pointer_buffer: bool,
+    pub(crate) raw_data_words: usize,
+    pub(crate) inline_buffer_len: usize,
+    pub(crate) bytes: Box<[u8]>
+}
+
+impl BoxedCommand {
+    /// Copies every section of a parsed command, so that it can be forwarded as-is or after
+    /// remapping its buffers
+    ///
+    /// The bytes are copied verbatim, so serializing the result without changes reproduces
+    /// the parsed command exactly. Command types this crate does not know about are reported
+    /// as [`CommandType::Invalid`] by [`BoxedCommand::ty`], but are still forwarded untouched.
+    ///
+    /// # Arguments
+    /// * `view` - The command to forward
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{boxed::BoxedCommand, command::{self, parse::ParsedCommand}, header, packed::BufferDescriptor};
+    ///
+    /// let received = command::request_builder()
+    ///     .with_special_header(header::new_builder().with_program_id(0).with_copy_handle(0xFFFF_8001))
+    ///     .with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+    ///     .with_raw_data([1u32, 2, 3])
+    ///     .with_pointer_buffer_sized(0x8000, 0x200)
+    ///     .build();
+    ///
+    /// let view = ParsedCommand::try_from(&received[..]).unwrap();
+    /// let mut forwarded = BoxedCommand::forward_from(&view);
+    /// assert_eq!(forwarded.as_bytes(), &received);
+    /// assert_eq!(forwarded.num_send_buffers(), 1);
+    /// assert_eq!(forwarded.special_header_bytes(), 16);
+    /// assert_eq!(forwarded.raw_data_words(), 3);
+    /// assert!(forwarded.has_pointer_buffer());
+    ///
+    /// // Remap the send buffer into the proxy's address space
+    /// let offset = command::section_offsets(0, 1, 0, 0, 0, 3, 0, 16, true).send_buffers;
+    /// let remapped = BufferDescriptor::new(0x7_0000_1000, 0x100, 0);
+    /// forwarded.as_bytes_mut()[offset..offset + 12].copy_from_slice(&<[u8; 12]>::from(remapped));
+    /// assert_eq!(&forwarded.as_bytes()[..offset], &received[..offset]);
+    /// assert_eq!(&forwarded.as_bytes()[offset + 12..], &received[offset + 12..]);
+    /// ```
+    pub fn forward_from(view: &ParsedCommand) -> Self {
+        let header = view.header();
+        let mode = header.receive_static_mode() as usize;
+        let special_header_bytes = view.special_header().map_or(0, |special| special.byte_len());
+
+        let raw_data_end = section_offsets(
+            header.num_send_statics(),
+            header.num_send_buffers(),
+            header.num_receive_buffers(),
+            header.num_exchange_buffers(),
+            0,
+            header.raw_data_len(),
+            0,
+            special_header_bytes,
+            false
+        ).inline_buffer;
+
+        let inline_buffer_len = if mode == 1 {
+            view.len().saturating_sub(helpers::align_up(raw_data_end, 16))
+        } else {
+            0
+        };
+
+        let ty = CommandType::ALL
+            .into_iter()
+            .find(|ty| *ty as u16 == header.ty())
+            .unwrap_or(CommandType::Invalid);
+
+        Self {
+            ty,
+            send_statics: header.num_send_statics(),
+            send_buffers: header.num_send_buffers(),
+            recv_buffers: header.num_receive_buffers(),
+            exch_buffers: header.num_exchange_buffers(),
+            recv_statics: mode.saturating_sub(2),
+            special_header_bytes,
+            has_pointer_buffer: mode == 2,
+            raw_data_words: header.raw_data_len(),
+            inline_buffer_len,
+            bytes: view.as_bytes().into()
+        }
+    }
+
+    /// The type of the command
+    pub fn ty(&self) -> CommandType {
+        self.ty
+    }
+
+    /// The number of send statics in the command
+    pub fn num_send_statics(&self) -> usize {
+        self.send_statics
+    }
+
+    /// The number of send buffers in the command
+    pub fn num_send_buffers(&self) -> usize {
+        self.send_buffers
+    }
+
+    /// The number of receive buffers in the command
+    pub fn num_recv_buffers(&self) -> usize {
+        self.recv_buffers
+    }
+
+    /// The number of exchange buffers in the command
+    pub fn num_exch_buffers(&self) -> usize {
+        self.exch_buffers
+    }
+
+    /// The number of entries in the command's receive list, not counting a pointer buffer
+    pub fn num_recv_statics(&self) -> usize {
+        self.recv_statics
+    }
+
+    /// If the command has a special header
+    pub fn has_special_header(&self) -> bool {
+        self.special_header_bytes != 0
+    }
+
+    /// The size of the command's special header, in bytes
+    pub fn special_header_bytes(&self) -> usize {
+        self.special_header_bytes
+    }
+
+    /// If the command has a pointer buffer
+    pub fn has_pointer_buffer(&self) -> bool {
+        self.has_pointer_buffer
+    }
+
+    /// The number of 32-bit words in the command's raw data
+    pub fn raw_data_words(&self) -> usize {
+        self.raw_data_words
+    }
+
+    /// The size of the command's inline buffer, in bytes
+    pub fn inline_buffer_len(&self) -> usize {
+        self.inline_buffer_len
+    }
+
+    /// The serialized command
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// The serialized command, for editing in place (such as remapping buffer addresses)
+    ///
+    /// The section counts are not updated to match any edits, so only the contents of
+    /// sections should be changed, never the header.
+    pub fn as_bytes_mut(&mut self) -> &mut [u8] {
+        &mut self.bytes
+    }
+
+    /// Takes the serialized command out of the box
+    pub fn into_bytes(self) -> Box<[u8]> {
+        self.bytes
+    }
+}
@@ -0,0 +1,665 @@
+use crate::{command::{helpers, section_offsets}, domain::{DomainCommandKind, DomainRequestHeader}, util::{const_assert, read_word}};
+
+/// The alignment, in bytes, that the CMIF region must start at within the command
+pub const CMIF_ALIGNMENT: usize = 0x10;
+
+/// The size of the CMIF request/response header, in bytes
+pub const CMIF_HEADER_SIZE: usize = 0x10;
+
+/// The size of the domain header which precedes the CMIF header in domain commands, in bytes
+pub const DOMAIN_HEADER_SIZE: usize = 0x10;
+
+/// The magic value at the start of every CMIF request header (`"SFCI"`)
+pub const REQUEST_MAGIC: u32 = u32::from_le_bytes(*b"SFCI");
+
+/// The magic value at the start of every CMIF response header (`"SFCO"`)
+pub const RESPONSE_MAGIC: u32 = u32::from_le_bytes(*b"SFCO");
+
+/// The CMIF header version written by context commands
+///
+/// A [`RequestWithContext`](crate::CommandType::RequestWithContext) or
+/// [`ControlWithContext`](crate::CommandType::ControlWithContext) command carries its context
+/// token inside the CMIF request header rather than in the HIPC layout. The header's second
+/// word holds this version instead of 0, and the token is written to its last word, which is
+/// left zeroed by other commands:
+///
+/// | Word | Request | Request with context |
+/// | ---- | ------- | -------------------- |
+/// | `0` | Magic (`"SFCI"`) | Magic (`"SFCI"`) |
+/// | `1` | `0` | `CONTEXT_VERSION` |
+/// | `2` | Command ID | Command ID |
+/// | `3` | `0` | Token |
+pub const CONTEXT_VERSION: u32 = 1;
+
+/// The index of the version word within the CMIF header
+const VERSION_WORD: usize = 1;
+
+/// The index of the token word within the CMIF header
+const TOKEN_WORD: usize = 3;
+
+/// The number of words in the CMIF header
+const CMIF_HEADER_WORDS: usize = CMIF_HEADER_SIZE / core::mem::size_of::<u32>();
+
+/// Errors produced when decoding the CMIF region of a command
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CmifError {
+    /// The raw data is too short to contain the CMIF header
+    Truncated,
+
+    /// The CMIF header does not begin with the expected magic value
+    InvalidMagic(u32),
+
+    /// The server responded with a failing result code
+    Failure(u32),
+
+    /// The response does not carry the handle the command returns
+    MissingHandle
+}
+
+/// Byte offsets, from the start of the command, of each piece of the CMIF region
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Layout {
+    /// The start of the HIPC raw data region, directly after the descriptors
+    pub raw_data: usize,
+
+    /// The domain header, if the command targets a domain object
+    pub domain_header: Option<usize>,
+
+    /// The CMIF header
+    pub header: usize,
+
+    /// The command arguments, directly following the CMIF header
+    pub arguments: usize
+}
+
+impl Layout {
+    /// The number of raw data words needed to hold the padding, headers, and `args_len` bytes
+    /// of arguments
+    ///
+    /// # Arguments
+    /// * `args_len` - The length, in bytes, of the command arguments
+    pub const fn raw_data_len(self, args_len: usize) -> usize {
+        helpers::align_up(self.arguments - self.raw_data + args_len, core::mem::size_of::<u32>()) / core::mem::size_of::<u32>()
+    }
+}
+
+/// Computes where the CMIF structures land inside of a HIPC command
+///
+/// The CMIF region starts at the first 16-byte aligned offset inside of the raw data, and
+/// when a domain header is present it sits directly before the CMIF header.
+///
+/// # Arguments
+/// * `send_statics` - The number of statics
+/// * `send_buffers` - The number of send buffers
+/// * `recv_buffers` - The number of receive buffers
+/// * `exch_buffers` - The number of exchange buffers
+/// * `special_header_total` - The number of bytes the special header consumes (0 if not present)
+/// * `has_domain_header` - If the command targets a domain object
+///
+/// # Example
+/// ```
+/// use hipc::{cmif, header};
+///
+/// // Nothing but the header, the CMIF region is padded out to the next 16 bytes
+/// let layout = cmif::layout(0, 0, 0, 0, 0, false);
+/// assert_eq!(layout.raw_data, 0x8);
+/// assert_eq!(layout.header, 0x10);
+/// assert_eq!(layout.arguments, 0x20);
+///
+/// // 8 bytes of padding, 16 bytes of header, and 8 bytes of arguments
+/// assert_eq!(layout.raw_data_len(8), 8);
+///
+/// // The domain header comes first
+/// let layout = cmif::layout(0, 0, 0, 0, 0, true);
+/// assert_eq!(layout.domain_header, Some(0x10));
+/// assert_eq!(layout.header, 0x20);
+/// assert_eq!(layout.arguments, 0x30);
+///
+/// // A special header with a single copy handle lands the raw data on an aligned offset
+/// let special = header::new_builder().with_copy_handle(0xFFFF_8001);
+/// let layout = cmif::layout(0, 0, 0, 0, special.build().len(), false);
+/// assert_eq!(layout.raw_data, 0x10);
+/// assert_eq!(layout.header, 0x10);
+///
+/// // A send buffer after that pushes it out of alignment again
+/// let layout = cmif::layout(0, 1, 0, 0, special.build().len(), true);
+/// assert_eq!(layout.raw_data, 0x1C);
+/// assert_eq!(layout.domain_header, Some(0x20));
+/// assert_eq!(layout.header, 0x30);
+/// assert_eq!(layout.arguments, 0x40);
+/// ```
+pub const fn layout(
+    send_statics: usize,
+    send_buffers: usize,
+    recv_buffers: usize,
+    exch_buffers: usize,
+    special_header_total: usize,
+    has_domain_header: bool
+) -> Layout
+{
+    let raw_data = section_offsets(
+        send_statics,
+        send_buffers,
+        recv_buffers,
+        exch_buffers,
+        0,
+        0,
+        0,
+        special_header_total,
+        false
+    ).raw_data;
+
+    let start = helpers::pre_raw_data_words(
+        send_statics,
+        send_buffers,
+        recv_buffers,
+        exch_buffers,
+        special_header_total
+    ) * core::mem::size_of::<u32>();
+
+    let (domain_header, header) = if has_domain_header {
+        (Some(start), start + DOMAIN_HEADER_SIZE)
+    } else {
+        (None, start)
+    };
+
+    Layout {
+        raw_data,
+        domain_header,
+        header,
+        arguments: header + CMIF_HEADER_SIZE
+    }
+}
+
+/// Whether a CMIF request goes to a plain session or to an object within a domain
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CmifMode {
+    /// The session itself is the target, the raw data holds only the CMIF header and arguments
+    Session,
+
+    /// An object within a domain is the target, a domain header precedes the CMIF header and
+    /// any input object IDs follow the arguments
+    Domain {
+        /// The ID of the target object
+        object_id: u32
+    }
+}
+
+impl CmifMode {
+    /// If requests in this mode carry a domain header
+    pub const fn has_domain_header(self) -> bool {
+        matches!(self, CmifMode::Domain { .. })
+    }
+}
+
+/// The number of padding words between the start of the raw data and the 16-byte aligned
+/// start of the CMIF region
+const fn header_padding_words(raw_data_offset: usize) -> usize {
+    let start = helpers::align_up(raw_data_offset, CMIF_ALIGNMENT);
+    (start - raw_data_offset) / core::mem::size_of::<u32>()
+}
+
+/// The number of raw data words [`request_words`] produces
+///
+/// # Arguments
+/// * `raw_data_offset` - The byte offset of the raw data from the start of the command
+/// * `mode` - The target of the request
+/// * `num_args` - The number of argument words
+/// * `num_in_objects` - The number of input object IDs, always 0 in [`CmifMode::Session`]
+pub const fn request_len(raw_data_offset: usize, mode: CmifMode, num_args: usize, num_in_objects: usize) -> usize {
+    let padding = header_padding_words(raw_data_offset);
+    let domain = if mode.has_domain_header() {
+        DOMAIN_HEADER_SIZE / core::mem::size_of::<u32>() + num_in_objects
+    } else {
+        0
+    };
+
+    padding + domain + CMIF_HEADER_SIZE / core::mem::size_of::<u32>() + num_args
+}
+
+/// Lays out the raw data of a CMIF request, for either a session or a domain object
+///
+/// In [`CmifMode::Domain`] a `SendMessage` domain header is inserted before the CMIF header,
+/// and the input object IDs are appended after the arguments. The result can be passed
+/// directly to [`with_raw_data`](crate::command::HipcCommandBuilder::with_raw_data).
+///
+/// # Arguments
+/// * `raw_data_offset` - The byte offset of the raw data from the start of the command
+/// * `mode` - The target of the request
+/// * `command_id` - The CMIF command ID
+/// * `args` - The argument words
+/// * `in_objects` - The input object IDs, which must be empty in [`CmifMode::Session`]
+///
+/// # Panicking
+/// * `LEN` is not [`request_len`] for the same arguments
+/// * `in_objects` is not empty in [`CmifMode::Session`]
+///
+/// # Example
+/// ```
+/// use hipc::{cmif::{self, CmifMode}, command};
+///
+/// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+/// const SESSION: CmifMode = CmifMode::Session;
+/// const DOMAIN: CmifMode = CmifMode::Domain { object_id: 1 };
+///
+/// // Padding, CMIF header, and arguments
+/// let words: [u32; cmif::request_len(OFFSET, SESSION, 2, 0)] =
+///     cmif::request_words(OFFSET, SESSION, 42, [0xAAAA_AAAA, 0xBBBB_BBBB], &[]);
+/// assert_eq!(words, [
+///     0, 0,
+///     cmif::REQUEST_MAGIC, 0, 42, 0,
+///     0xAAAA_AAAA, 0xBBBB_BBBB
+/// ]);
+///
+/// // Padding, domain header, CMIF header, arguments, and input object IDs
+/// let words: [u32; cmif::request_len(OFFSET, DOMAIN, 2, 2)] =
+///     cmif::request_words(OFFSET, DOMAIN, 42, [0xAAAA_AAAA, 0xBBBB_BBBB], &[5, 6]);
+/// assert_eq!(words, [
+///     0, 0,
+///     0x0018_0201, 1, 0, 0,
+///     cmif::REQUEST_MAGIC, 0, 42, 0,
+///     0xAAAA_AAAA, 0xBBBB_BBBB,
+///     5, 6
+/// ]);
+///
+/// // The domain header lands where the layout expects it
+/// let bytes = command::request_builder().with_raw_data(words).build();
+/// let layout = cmif::layout(0, 0, 0, 0, 0, true);
+/// assert_eq!(&bytes[layout.domain_header.unwrap()..][..4], &0x0018_0201u32.to_le_bytes());
+/// assert_eq!(&bytes[layout.header..][..4], b"SFCI");
+/// ```
+#[track_caller]
+pub const fn request_words<const N: usize, const LEN: usize>(
+    raw_data_offset: usize,
+    mode: CmifMode,
+    command_id: u32,
+    args: [u32; N],
+    in_objects: &[u32]
+) -> [u32; LEN]
+{
+    let expected = request_len(raw_data_offset, mode, N, in_objects.len());
+    const_assert!(
+        LEN == expected,
+        "The output length does not match cmif::request_len",
+        "output length" => LEN,
+        "request_len" => expected
+    );
+
+    let mut words = [0u32; LEN];
+    let mut cursor = header_padding_words(raw_data_offset);
+
+    if let CmifMode::Domain { object_id } = mode {
+        let header = DomainRequestHeader::new(
+            DomainCommandKind::SendMessage,
+            in_objects.len(),
+            CMIF_HEADER_SIZE + N * core::mem::size_of::<u32>(),
+            object_id
+        ).words();
+
+        let mut index = 0;
+        while index < header.len() {
+            words[cursor] = header[index];
+            cursor += 1;
+            index += 1;
+        }
+    } else {
+        const_assert!(
+            in_objects.is_empty(),
+            "Input objects can only be sent to a domain object",
+            "input objects" => in_objects.len()
+        );
+    }
+
+    let header = [REQUEST_MAGIC, 0, command_id, 0];
+    let mut index = 0;
+    while index < header.len() {
+        words[cursor] = header[index];
+        cursor += 1;
+        index += 1;
+    }
+
+    index = 0;
+    while index < N {
+        words[cursor] = args[index];
+        cursor += 1;
+        index += 1;
+    }
+
+    index = 0;
+    while index < in_objects.len() {
+        words[cursor] = in_objects[index];
+        cursor += 1;
+        index += 1;
+    }
+
+    words
+}
+
+/// Writes a context token into the CMIF request header within a command's raw data
+///
+/// The CMIF header is expected at the 16-byte aligned start of the CMIF region, or directly
+/// after a domain header there. Its version word is set to [`CONTEXT_VERSION`] and its last
+/// word to `token`.
+///
+/// # Arguments
+/// * `words` - The raw data of the command
+/// * `raw_data_offset` - The byte offset of the raw data from the start of the command
+/// * `token` - The context token
+///
+/// # Panicking
+/// * The raw data does not contain a CMIF request header where one is expected
+///
+/// # Example
+/// ```
+/// use hipc::cmif::{self, CmifMode};
+///
+/// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+///
+/// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 1, 0)] =
+///     cmif::request_words(OFFSET, CmifMode::Session, 42, [7], &[]);
+/// assert_eq!(cmif::with_context_token(words, OFFSET, 0x1234), [
+///     0, 0,
+///     cmif::REQUEST_MAGIC, cmif::CONTEXT_VERSION, 42, 0x1234,
+///     7
+/// ]);
+///
+/// // Behind a domain header
+/// const DOMAIN: CmifMode = CmifMode::Domain { object_id: 1 };
+/// let words: [u32; cmif::request_len(OFFSET, DOMAIN, 1, 0)] = cmif::request_words(OFFSET, DOMAIN, 42, [7], &[]);
+/// assert_eq!(&cmif::with_context_token(words, OFFSET, 0x1234)[6..10], &[cmif::REQUEST_MAGIC, 1, 42, 0x1234]);
+/// ```
+#[track_caller]
+pub const fn with_context_token<const LEN: usize>(mut words: [u32; LEN], raw_data_offset: usize, token: u32) -> [u32; LEN] {
+    let header = request_header_word(&words, raw_data_offset);
+
+    const_assert!(header.is_some(), "No CMIF request header found in the raw data", "raw data words" => LEN);
+    let header = header.unwrap();
+
+    words[header + VERSION_WORD] = CONTEXT_VERSION;
+    words[header + TOKEN_WORD] = token;
+    words
+}
+
+/// Reads the context token from the CMIF request in a command's raw data words
+///
+/// This is [`request_context_token`] for a command that has not been serialized yet, where
+/// anything that is not a CMIF request with a token is treated as carrying none.
+pub(crate) const fn words_context_token(words: &[u32], raw_data_offset: usize) -> Option<u32> {
+    match request_header_word(words, raw_data_offset) {
+        Some(header) if words[header + VERSION_WORD] == CONTEXT_VERSION => Some(words[header + TOKEN_WORD]),
+        _ => None
+    }
+}
+
+/// Finds the word index of the CMIF request header in a command's raw data, which is either at
+/// the start of the CMIF region or directly after a domain header there
+///
+/// # Returns
+/// * `None` if neither position holds a whole CMIF request header
+pub(crate) const fn request_header_word(words: &[u32], raw_data_offset: usize) -> Option<usize> {
+    let mut header = header_padding_words(raw_data_offset);
+    if header < words.len() && words[header] != REQUEST_MAGIC {
+        header += DOMAIN_HEADER_SIZE / core::mem::size_of::<u32>();
+    }
+
+    if header + CMIF_HEADER_WORDS <= words.len() && words[header] == REQUEST_MAGIC {
+        Some(header)
+    } else {
+        None
+    }
+}
+
+/// Inserts a domain header into a command's raw data, in front of the CMIF request it holds
+///
+/// The raw data must start with the padding before the 16-byte aligned CMIF region, as laid
+/// out by [`request_words`] in [`CmifMode::Session`]. The domain header is placed at the start
+/// of the CMIF region and its data length covers every word after it. A
+/// [`Close`](DomainCommandKind::Close) request carries no CMIF data, so its raw data must be
+/// only the padding.
+///
+/// # Arguments
+/// * `words` - The raw data of the command
+/// * `raw_data_offset` - The byte offset of the raw data from the start of the command
+/// * `kind` - The operation to perform on the object
+/// * `object_id` - The ID of the target object
+///
+/// # Panicking
+/// * `OUT` is not `LEN` plus the size of the domain header in words
+/// * The raw data is shorter than the padding before the CMIF region
+/// * `kind` is [`Close`](DomainCommandKind::Close) and the raw data holds more than the padding
+///
+/// # Example
+/// ```
+/// use hipc::{cmif::{self, CmifMode}, domain::DomainCommandKind};
+///
+/// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+///
+/// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 2, 0)] =
+///     cmif::request_words(OFFSET, CmifMode::Session, 42, [0xAAAA_AAAA, 0xBBBB_BBBB], &[]);
+/// let domain: [u32; 12] = cmif::with_domain_header(words, OFFSET, DomainCommandKind::SendMessage, 1);
+///
+/// // The same words as laying the request out for the domain object directly
+/// let expected: [u32; cmif::request_len(OFFSET, CmifMode::Domain { object_id: 1 }, 2, 0)] =
+///     cmif::request_words(OFFSET, CmifMode::Domain { object_id: 1 }, 42, [0xAAAA_AAAA, 0xBBBB_BBBB], &[]);
+/// assert_eq!(domain, expected);
+///
+/// // Closing the object only needs the padding
+/// let close: [u32; 6] = cmif::with_domain_header([0u32; 2], OFFSET, DomainCommandKind::Close, 1);
+/// assert_eq!(close, [0, 0, 0x0000_0002, 1, 0, 0]);
+///
+/// let panic = std::panic::catch_unwind(|| -> [u32; 4] {
+///     cmif::with_domain_header([0u32; 2], OFFSET, DomainCommandKind::Close, 1)
+/// }).unwrap_err();
+/// assert_eq!(
+///     panic.downcast_ref::<String>().unwrap(),
+///     "The output length does not include the domain header (output length: 4, input length: 2, domain header words: 4)"
+/// );
+/// ```
+#[track_caller]
+pub const fn with_domain_header<const LEN: usize, const OUT: usize>(
+    words: [u32; LEN],
+    raw_data_offset: usize,
+    kind: DomainCommandKind,
+    object_id: u32
+) -> [u32; OUT]
+{
+    const HEADER_WORDS: usize = DOMAIN_HEADER_SIZE / core::mem::size_of::<u32>();
+
+    const_assert!(
+        OUT == LEN + HEADER_WORDS,
+        "The output length does not include the domain header",
+        "output length" => OUT,
+        "input length" => LEN,
+        "domain header words" => HEADER_WORDS
+    );
+
+    let padding = header_padding_words(raw_data_offset);
+    const_assert!(
+        LEN >= padding,
+        "The raw data does not reach the start of the CMIF region",
+        "raw data words" => LEN,
+        "padding words" => padding
+    );
+
+    let data_len = (LEN - padding) * core::mem::size_of::<u32>();
+    const_assert!(
+        !matches!(kind, DomainCommandKind::Close) || data_len == 0,
+        "Closing a domain object does not carry any CMIF data",
+        "data length" => data_len
+    );
+
+    let header = DomainRequestHeader::new(kind, 0, data_len, object_id).words();
+
+    let mut out = [0u32; OUT];
+    let mut index = 0;
+    while index < LEN {
+        let target = if index < padding { index } else { index + HEADER_WORDS };
+        out[target] = words[index];
+        index += 1;
+    }
+
+    index = 0;
+    while index < HEADER_WORDS {
+        out[padding + index] = header[index];
+        index += 1;
+    }
+
+    out
+}
+
+/// Reads the context token from the CMIF request carried in a command's raw data
+///
+/// The CMIF header is found the same way as in [`with_context_token`].
+///
+/// # Arguments
+/// * `raw` - The raw data region of the command
+/// * `raw_data_offset` - The byte offset of `raw` from the start of the command
+///
+/// # Returns
+/// * `None` if the CMIF header's version is not [`CONTEXT_VERSION`], the command has no token
+///
+/// # Failures
+/// * The raw data ends before the end of the CMIF header
+/// * Neither position a CMIF header can be at begins with [`REQUEST_MAGIC`]
+///
+/// # Example
+/// ```
+/// use hipc::cmif::{self, CmifError};
+///
+/// let mut raw = [0u8; 0x18];
+/// raw[0x08..0x0C].copy_from_slice(b"SFCI");
+/// assert_eq!(cmif::request_context_token(&raw, 8), Ok(None));
+///
+/// raw[0x0C..0x10].copy_from_slice(&cmif::CONTEXT_VERSION.to_le_bytes());
+/// raw[0x14..0x18].copy_from_slice(&0x1234u32.to_le_bytes());
+/// assert_eq!(cmif::request_context_token(&raw, 8), Ok(Some(0x1234)));
+///
+/// assert_eq!(cmif::request_context_token(&raw[..0x10], 8), Err(CmifError::Truncated));
+/// ```
+pub const fn request_context_token(raw: &[u8], raw_data_offset: usize) -> Result<Option<u32>, CmifError> {
+    let header = request_header_offset(raw, raw_data_offset);
+    if raw.len() < header + CMIF_HEADER_SIZE {
+        return Err(CmifError::Truncated);
+    }
+
+    let magic = read_word(raw, header);
+    if magic != REQUEST_MAGIC {
+        return Err(CmifError::InvalidMagic(magic));
+    }
+
+    let word_size = core::mem::size_of::<u32>();
+    if read_word(raw, header + VERSION_WORD * word_size) != CONTEXT_VERSION {
+        return Ok(None);
+    }
+
+    Ok(Some(read_word(raw, header + TOKEN_WORD * word_size)))
+}
+
+/// Finds the byte offset of the CMIF request header in a command's raw data, skipping a domain
+/// header when the start of the CMIF region does not hold [`REQUEST_MAGIC`]
+const fn request_header_offset(raw: &[u8], raw_data_offset: usize) -> usize {
+    let header = helpers::align_up(raw_data_offset, CMIF_ALIGNMENT) - raw_data_offset;
+    if raw.len() >= header + core::mem::size_of::<u32>() && read_word(raw, header) != REQUEST_MAGIC {
+        header + DOMAIN_HEADER_SIZE
+    } else {
+        header
+    }
+}
+
+/// Extracts the command ID from the CMIF request carried in a command's raw data
+///
+/// The CMIF header is found the same way as in [`with_context_token`], so a request to a
+/// domain object is read past its domain header.
+///
+/// # Arguments
+/// * `raw` - The raw data region of the command
+/// * `raw_data_offset` - The byte offset of `raw` from the start of the command, used to find
+///   the 16-byte aligned start of the CMIF header
+///
+/// # Failures
+/// * The raw data ends before the end of the CMIF header
+/// * Neither position a CMIF header can be at begins with [`REQUEST_MAGIC`]
+///
+/// # Example
+/// ```
+/// use hipc::cmif::{self, CmifError};
+///
+/// // The raw data starts right after the 8-byte HIPC header, so the CMIF header is padded out
+/// // by 8 bytes to reach offset 0x10
+/// let mut raw = [0u8; 0x18];
+/// raw[0x08..0x0C].copy_from_slice(b"SFCI");
+/// raw[0x10..0x14].copy_from_slice(&42u32.to_le_bytes());
+///
+/// assert_eq!(cmif::request_command_id(&raw, 8), Ok(42));
+/// assert_eq!(cmif::request_command_id(&raw[..0x14], 8), Err(CmifError::Truncated));
+///
+/// // Behind a domain header
+/// let mut domain = [0u8; 0x28];
+/// domain[0x08] = 1;
+/// domain[0x18..0x1C].copy_from_slice(b"SFCI");
+/// domain[0x20..0x24].copy_from_slice(&42u32.to_le_bytes());
+/// assert_eq!(cmif::request_command_id(&domain, 8), Ok(42));
+///
+/// // Neither position holds a request header
+/// raw[0x08..0x0C].copy_from_slice(b"SFCO");
+/// assert_eq!(cmif::request_command_id(&raw, 8), Err(CmifError::Truncated));
+/// assert_eq!(cmif::request_command_id(&domain[..0x18], 8), Err(CmifError::Truncated));
+///
+/// domain[0x18..0x1C].copy_from_slice(b"SFCO");
+/// assert_eq!(cmif::request_command_id(&domain, 8), Err(CmifError::InvalidMagic(cmif::RESPONSE_MAGIC)));
+/// ```
+pub const fn request_command_id(raw: &[u8], raw_data_offset: usize) -> Result<u32, CmifError> {
+    let header = request_header_offset(raw, raw_data_offset);
+
+    if raw.len() < header + CMIF_HEADER_SIZE {
+        return Err(CmifError::Truncated);
+    }
+
+    let magic = read_word(raw, header);
+    if magic != REQUEST_MAGIC {
+        return Err(CmifError::InvalidMagic(magic));
+    }
+
+    Ok(read_word(raw, header + 8))
+}
+
+/// Checks the result code of the CMIF response carried in a command's raw data
+///
+/// # Arguments
+/// * `raw` - The raw data region of the response
+/// * `raw_data_offset` - The byte offset of `raw` from the start of the response, used to find
+///   the 16-byte aligned start of the CMIF header
+///
+/// # Failures
+/// * The raw data ends before the end of the CMIF header
+/// * The CMIF header does not begin with [`RESPONSE_MAGIC`]
+/// * The result code is not zero
+///
+/// # Example
+/// ```
+/// use hipc::cmif::{self, CmifError};
+///
+/// let mut raw = [0u8; 0x18];
+/// raw[0x08..0x0C].copy_from_slice(b"SFCO");
+/// assert_eq!(cmif::response_result(&raw, 8), Ok(()));
+///
+/// raw[0x10..0x14].copy_from_slice(&0x1A8u32.to_le_bytes());
+/// assert_eq!(cmif::response_result(&raw, 8), Err(CmifError::Failure(0x1A8)));
+/// ```
+pub const fn response_result(raw: &[u8], raw_data_offset: usize) -> Result<(), CmifError> {
+    let header = helpers::align_up(raw_data_offset, CMIF_ALIGNMENT) - raw_data_offset;
+
+    if raw.len() < header + CMIF_HEADER_SIZE {
+        return Err(CmifError::Truncated);
+    }
+
+    let magic = read_word(raw, header);
+    if magic != RESPONSE_MAGIC {
+        return Err(CmifError::InvalidMagic(magic));
+    }
+
+    match read_word(raw, header + 8) {
+        0 => Ok(()),
+        result => Err(CmifError::Failure(result))
+    }
+}
@@ -1,4 +1,9 @@
-use crate::{packed::*, CommandType, IntoWords, IntoBytes, header::SpecialHeaderBuilder};
+use core::{convert::Infallible, mem::MaybeUninit};
+
+use crate::{packed::*, CommandType, IntoWords, IntoBytes, domain::DomainCommandKind, header::SpecialHeaderBuilder, util::const_assert};
+
+pub mod parse;
+pub mod runtime;
 
 
 /// The maximum number of statics/in pointers the command can hold
@@ -27,6 +32,341 @@ const MAX_POINTER_BUFS: usize = 0x01;
 /// The maximum size of the command (since it goes on the TLS)
 const MAX_TLS_BUFFER_SIZE: usize = 0x100;
 
+/// The maximum number of raw data words the header's 10-bit length field can describe
+const MAX_RAW_DATA_WORDS: usize = 0x3FF;
+
+/// The maximum number of descriptors the command can hold across every section
+///
+/// Every descriptor takes at least 8 bytes, so no more than this many fit on the TLS after the
+/// header, regardless of how they are split between sections.
+pub const MAX_DESCRIPTORS: usize = (MAX_TLS_BUFFER_SIZE - core::mem::size_of::<Header>()) / 8;
+
+/// Errors produced when validating a command
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// One of the command's buffer descriptors is malformed
+    InvalidBuffer(DescriptorError),
+
+    /// Two of the command's buffers map overlapping address ranges
+    OverlappingBuffers,
+
+    /// The command is a context command but carries no context token
+    MissingContextToken,
+
+    /// The command carries a context token but is not a context command
+    UnexpectedContextToken,
+
+    /// The command was given a context token but its raw data has no CMIF request header to
+    /// hold it
+    NoContextTokenHeader
+}
+
+/// A section of a command with a limited number of entries
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Section {
+    /// InPointers/"Send Statics"
+    SendStatics,
+
+    /// InMapAlias/"Send Buffers"
+    SendBuffers,
+
+    /// OutMapAlias/"Receive Buffers"
+    RecvBuffers,
+
+    /// InOutMapAlias/"Exchange Buffers"
+    ExchBuffers,
+
+    /// OutPointers/"Receive Statics"
+    RecvStatics,
+
+    /// The special header
+    SpecialHeader,
+
+    /// The pointer buffer
+    PointerBuffer
+}
+
+impl Section {
+    /// The maximum number of entries a command can hold in this section
+    pub const fn max_count(self) -> usize {
+        match self {
+            Section::SendStatics => MAX_SEND_STATICS,
+            Section::SendBuffers => MAX_SEND_BUFFERS,
+            Section::RecvBuffers => MAX_RECV_BUFFERS,
+            Section::ExchBuffers => MAX_EXCH_BUFFERS,
+            Section::RecvStatics => MAX_RECV_STATICS,
+            Section::SpecialHeader => MAX_SPECIAL_HDRS,
+            Section::PointerBuffer => MAX_POINTER_BUFS
+        }
+    }
+}
+
+/// Errors produced when a command's shape is not allowed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum BuilderError {
+    /// A section has more entries than it can hold
+    SectionFull {
+        /// The section that is full
+        section: Section,
+
+        /// The maximum number of entries the section can hold
+        max: usize
+    },
+
+    /// The command is too large to fit in the TLS
+    TlsOverflow {
+        /// The number of bytes the command needs
+        needed: usize,
+
+        /// The number of bytes available
+        max: usize
+    },
+
+    /// More than one of receive statics, an inline buffer, and a pointer buffer were used
+    IllegalRecvList,
+
+    /// The buffer being serialized into cannot hold the command
+    BufferTooSmall {
+        /// The number of bytes the command needs
+        needed: usize,
+
+        /// The number of bytes the buffer can hold
+        available: usize
+    },
+
+    /// The special header has more copy or move handles than it can hold
+    TooManyHandles {
+        /// The maximum number of handles of each kind
+        max: usize
+    },
+
+    /// The raw data is longer than the header's length field can describe
+    RawDataTooLong {
+        /// The number of raw data words
+        len: usize,
+
+        /// The maximum number of raw data words
+        max: usize
+    },
+
+    /// The command is a context command but carries no context token
+    MissingContextToken,
+
+    /// The command carries a context token but is not a context command
+    UnexpectedContextToken,
+
+    /// The command was given a context token but its raw data has no CMIF request header to
+    /// hold it
+    NoContextTokenHeader
+}
+
+/// Formats the error as a short description, including the limit that was exceeded
+///
+/// # Example
+/// ```
+/// use hipc::command::{BuilderError, Section};
+///
+/// assert_eq!(
+///     BuilderError::SectionFull { section: Section::SendStatics, max: 15 }.to_string(),
+///     "Too many send statics, the maximum is 15"
+/// );
+/// assert_eq!(
+///     BuilderError::TlsOverflow { needed: 0x108, max: 0x100 }.to_string(),
+///     "Command needs 264 bytes but the TLS only holds 256"
+/// );
+/// ```
+impl core::fmt::Display for BuilderError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            BuilderError::SectionFull { section, max } => {
+                let name = match section {
+                    Section::SendStatics => "send statics",
+                    Section::SendBuffers => "send buffers",
+                    Section::RecvBuffers => "receive buffers",
+                    Section::ExchBuffers => "exchange buffers",
+                    Section::RecvStatics => "receive statics",
+                    Section::SpecialHeader => "special headers",
+                    Section::PointerBuffer => "pointer buffers"
+                };
+
+                write!(f, "Too many {}, the maximum is {}", name, max)
+            },
+            BuilderError::TlsOverflow { needed, max } => {
+                write!(f, "Command needs {} bytes but the TLS only holds {}", needed, max)
+            },
+            BuilderError::IllegalRecvList => {
+                f.write_str("Only one of receive statics, an inline buffer, and a pointer buffer can be used")
+            },
+            BuilderError::BufferTooSmall { needed, available } => {
+                write!(f, "Command needs {} bytes but the buffer only holds {}", needed, available)
+            },
+            BuilderError::TooManyHandles { max } => {
+                write!(f, "Too many handles of one kind, the maximum is {}", max)
+            },
+            BuilderError::RawDataTooLong { len, max } => {
+                write!(f, "Raw data has {} words but the header can only describe {}", len, max)
+            },
+            BuilderError::MissingContextToken => {
+                f.write_str("Context commands must carry a context token")
+            },
+            BuilderError::UnexpectedContextToken => {
+                f.write_str("Only context commands can carry a context token")
+            },
+            BuilderError::NoContextTokenHeader => {
+                f.write_str("The raw data has no CMIF request header to hold the context token")
+            }
+        }
+    }
+}
+
+/// Checks that a section can hold `count` entries
+///
+/// # Failures
+/// * `count` is greater than [`Section::max_count`]
+///
+/// # Example
+/// ```
+/// use hipc::command::{self, BuilderError, Section};
+///
+/// assert_eq!(command::check_section(Section::RecvStatics, 13), Ok(()));
+/// assert_eq!(
+///     command::check_section(Section::RecvStatics, 14),
+///     Err(BuilderError::SectionFull { section: Section::RecvStatics, max: 13 })
+/// );
+/// ```
+pub const fn check_section(section: Section, count: usize) -> Result<(), BuilderError> {
+    if count > section.max_count() {
+        Err(BuilderError::SectionFull { section, max: section.max_count() })
+    } else {
+        Ok(())
+    }
+}
+
+/// Checks that a command's receive list is legal and that the command fits in the TLS
+///
+/// This is the fallible counterpart to the panicking checks made while building a command at
+/// compile time.
+///
+/// # Arguments
+/// * `send_statics` - The number of statics
+/// * `send_buffers` - The number of send buffers
+/// * `recv_buffers` - The number of receive buffers
+/// * `exch_buffers` - The number of exchange buffers
+/// * `recv_statics` - The number of entries in the receive list
+/// * `raw_len` - The number of 32-bit words in the raw data payload
+/// * `inline_buff_len` - The number of bytes in the inlined receive list buffer
+/// * `special_header_total` - The number of bytes the special header consumes (0 if not present)
+/// * `has_pointer_buffer` - If the command has a pointer buffer for the receive list
+///
+/// # Returns
+/// * The total number of bytes the command consumes
+///
+/// # Failures
+/// * More than one of `recv_statics`, `inline_buff_len`, and `has_pointer_buffer` are set
+/// * The command is larger than the TLS
+///
+/// # Example
+/// ```
+/// use hipc::command::{self, BuilderError};
+///
+/// assert_eq!(command::check_layout(0, 1, 0, 0, 0, 2, 0, 0, false), Ok(8 + 12 + 8));
+/// assert_eq!(command::check_layout(0, 0, 0, 0, 1, 0, 0, 0, true), Err(BuilderError::IllegalRecvList));
+/// assert_eq!(
+///     command::check_layout(0, 0, 0, 0, 0, 0x40, 0, 0, false),
+///     Err(BuilderError::TlsOverflow { needed: 8 + 0x100, max: 0x100 })
+/// );
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub const fn check_layout(
+    send_statics: usize,
+    send_buffers: usize,
+    recv_buffers: usize,
+    exch_buffers: usize,
+    recv_statics: usize,
+    raw_len: usize,
+    inline_buff_len: usize,
+    special_header_total: usize,
+    has_pointer_buffer: bool
+) -> Result<usize, BuilderError>
+{
+    let recv_list_modes = (recv_statics != 0) as usize + (inline_buff_len != 0) as usize + has_pointer_buffer as usize;
+    if recv_list_modes > 1 {
+        return Err(BuilderError::IllegalRecvList);
+    }
+
+    let needed = section_offsets(
+        send_statics,
+        send_buffers,
+        recv_buffers,
+        exch_buffers,
+        recv_statics,
+        raw_len,
+        inline_buff_len,
+        special_header_total,
+        has_pointer_buffer
+    ).end;
+
+    if needed > MAX_TLS_BUFFER_SIZE {
+        return Err(BuilderError::TlsOverflow { needed, max: MAX_TLS_BUFFER_SIZE });
+    }
+
+    Ok(needed)
+}
+
+/// Checks that a command carries a context token exactly when its type calls for one
+///
+/// The token is looked for in the CMIF request header, as described by
+/// [`cmif::CONTEXT_VERSION`](crate::cmif::CONTEXT_VERSION). Command types other than requests
+/// and controls are not checked.
+///
+/// # Arguments
+/// * `ty` - The type of the command
+/// * `raw_data` - The raw data of the command
+/// * `raw_data_offset` - The byte offset of the raw data from the start of the command
+///
+/// # Failures
+/// * `ty` is a context type and the raw data has no CMIF request header carrying a token
+/// * `ty` is a request or control type and the raw data has a CMIF request header carrying a token
+///
+/// # Example
+/// ```
+/// use hipc::{cmif::{self, CmifMode}, command::{self, BuilderError}, CommandType};
+///
+/// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+///
+/// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 1, 0)] =
+///     cmif::request_words(OFFSET, CmifMode::Session, 42, [7], &[]);
+/// let with_token = cmif::with_context_token(words, OFFSET, 0x1234);
+///
+/// assert_eq!(command::check_context_token(CommandType::Request, &words, OFFSET), Ok(()));
+/// assert_eq!(command::check_context_token(CommandType::RequestWithContext, &with_token, OFFSET), Ok(()));
+/// assert_eq!(command::check_context_token(CommandType::Close, &[], OFFSET), Ok(()));
+///
+/// assert_eq!(
+///     command::check_context_token(CommandType::RequestWithContext, &words, OFFSET),
+///     Err(BuilderError::MissingContextToken)
+/// );
+/// assert_eq!(
+///     command::check_context_token(CommandType::ControlWithContext, &[], OFFSET),
+///     Err(BuilderError::MissingContextToken)
+/// );
+/// assert_eq!(
+///     command::check_context_token(CommandType::Request, &with_token, OFFSET),
+///     Err(BuilderError::UnexpectedContextToken)
+/// );
+/// ```
+pub const fn check_context_token(ty: CommandType, raw_data: &[u32], raw_data_offset: usize) -> Result<(), BuilderError> {
+    let has_token = crate::cmif::words_context_token(raw_data, raw_data_offset).is_some();
+
+    match ty {
+        CommandType::RequestWithContext | CommandType::ControlWithContext if !has_token => {
+            Err(BuilderError::MissingContextToken)
+        },
+        CommandType::Request | CommandType::Control if has_token => Err(BuilderError::UnexpectedContextToken),
+        _ => Ok(())
+    }
+}
+
 /// Command builder for a HIPC Command
 /// 
 /// # Generics
@@ -80,7 +420,430 @@ pub struct HipcCommandBuilder
     special_hdrs: [SpecialHeaderBuilder<SH_PIDS, SH_COPY, SH_MOVE, SH_TOTAL>; SH],
     pointer_bufs: [ReceiveListEntry; PB],
     raw_data: Data,
-    inline_buffer: InlineBuffer
+    inline_buffer: InlineBuffer,
+    context_token: Option<u32>
+}
+
+/// A command carrying only `RAW` words of raw data
+///
+/// When returning one of these aliases, bind the builder to a local first (or name the raw data
+/// length with a turbofish). Returning the chain directly checks the alias against the return
+/// type before the raw data length has been inferred, which fails to unify.
+///
+/// # Example
+/// ```
+/// use hipc::command::{self, SimpleRequest};
+///
+/// fn get_version(major: u32, minor: u32) -> SimpleRequest<4> {
+///     let cmd = command::request_builder().with_raw_data([0, 1, major, minor]);
+///     cmd
+/// }
+///
+/// assert_eq!(get_version(1, 2).build().len(), 8 + 4 * 4);
+/// ```
+pub type SimpleRequest<const RAW: usize> = HipcCommandBuilder<
+    0, 0, 0, 0, 0, 0, 0,
+    0, 0, 0, 0,
+    RAW, 0,
+    { helpers::consumed_space(0, 0, 0, 0, 0, RAW, 0, 0, false) },
+    [u32; RAW],
+    [u8; 0]
+>;
+
+/// A command carrying a single send buffer and `RAW` words of raw data
+///
+/// # Example
+/// ```
+/// use hipc::{command::{self, RequestWithSendBuffer}, packed::BufferDescriptor};
+///
+/// fn write(buffer: BufferDescriptor) -> RequestWithSendBuffer<1> {
+///     let cmd = command::request_builder()
+///         .with_send_buffer(buffer)
+///         .with_raw_data([0x20u32]);
+///     cmd
+/// }
+///
+/// assert_eq!(write(BufferDescriptor::new(0x1000, 0x100, 0)).build().len(), 8 + 12 + 4);
+/// ```
+pub type RequestWithSendBuffer<const RAW: usize> = HipcCommandBuilder<
+    0, 1, 0, 0, 0, 0, 0,
+    0, 0, 0, 0,
+    RAW, 0,
+    { helpers::consumed_space(0, 1, 0, 0, 0, RAW, 0, 0, false) },
+    [u32; RAW],
+    [u8; 0]
+>;
+
+/// A command carrying a single receive buffer and `RAW` words of raw data
+///
+/// # Example
+/// ```
+/// use hipc::{command::{self, RequestWithRecvBuffer}, packed::BufferDescriptor};
+///
+/// fn read(buffer: BufferDescriptor) -> RequestWithRecvBuffer<1> {
+///     let cmd = command::request_builder()
+///         .with_recv_buffer(buffer)
+///         .with_raw_data([0x10u32]);
+///     cmd
+/// }
+///
+/// assert_eq!(read(BufferDescriptor::new(0x1000, 0x100, 0)).build().len(), 8 + 12 + 4);
+/// ```
+pub type RequestWithRecvBuffer<const RAW: usize> = HipcCommandBuilder<
+    0, 0, 1, 0, 0, 0, 0,
+    0, 0, 0, 0,
+    RAW, 0,
+    { helpers::consumed_space(0, 0, 1, 0, 0, RAW, 0, 0, false) },
+    [u32; RAW],
+    [u8; 0]
+>;
+
+/// A command carrying the sender's process ID in its special header and `RAW` words of raw data
+///
+/// # Example
+/// ```
+/// use hipc::{command::{self, RequestWithPid}, header};
+///
+/// fn initialize() -> RequestWithPid<2> {
+///     let cmd = command::request_builder()
+///         .with_special_header(header::new_builder().with_program_id(0))
+///         .with_raw_data([0u32, 0]);
+///     cmd
+/// }
+///
+/// assert_eq!(initialize().build().len(), 8 + 12 + 2 * 4);
+/// ```
+pub type RequestWithPid<const RAW: usize> = HipcCommandBuilder<
+    0, 0, 0, 0, 0, 1, 0,
+    1, 0, 0, { crate::header::consumed_space(1, 0, 0) },
+    RAW, 0,
+    { helpers::consumed_space(0, 0, 0, 0, 0, RAW, 0, crate::header::consumed_space(1, 0, 0), false) },
+    [u32; RAW],
+    [u8; 0]
+>;
+
+/// A section of a serialized command
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum SectionKind {
+    /// The command header
+    Header,
+
+    /// The special header, including its process ID and handles
+    SpecialHeader,
+
+    /// The send statics
+    SendStatics,
+
+    /// The send buffers
+    SendBuffers,
+
+    /// The receive buffers
+    RecvBuffers,
+
+    /// The exchange buffers
+    ExchBuffers,
+
+    /// The raw data payload
+    RawData,
+
+    /// The inline buffer
+    InlineBuffer,
+
+    /// The pointer buffer receive list entry
+    PointerBuffer,
+
+    /// The receive static receive list entries
+    RecvStatics
+}
+
+/// The order in which the sections of a command are serialized
+///
+/// Both the layout computed by [`section_offsets`] and the serialization done by
+/// [`HipcCommandBuilder::build`] walk this array, so they cannot disagree on the order.
+///
+/// # Example
+/// ```
+/// use hipc::command::{self, SectionKind};
+///
+/// assert_eq!(command::SECTION_ORDER, [
+///     SectionKind::Header,
+///     SectionKind::SpecialHeader,
+///     SectionKind::SendStatics,
+///     SectionKind::SendBuffers,
+///     SectionKind::RecvBuffers,
+///     SectionKind::ExchBuffers,
+///     SectionKind::RawData,
+///     SectionKind::InlineBuffer,
+///     SectionKind::PointerBuffer,
+///     SectionKind::RecvStatics
+/// ]);
+///
+/// // Every section starts at or after the one before it
+/// let offsets = command::section_offsets(1, 1, 1, 1, 0, 3, 0x10, 8, false);
+/// for pair in command::SECTION_ORDER.windows(2) {
+///     assert!(offsets.of(pair[0]) <= offsets.of(pair[1]));
+/// }
+/// ```
+pub const SECTION_ORDER: [SectionKind; 10] = [
+    SectionKind::Header,
+    SectionKind::SpecialHeader,
+    SectionKind::SendStatics,
+    SectionKind::SendBuffers,
+    SectionKind::RecvBuffers,
+    SectionKind::ExchBuffers,
+    SectionKind::RawData,
+    SectionKind::InlineBuffer,
+    SectionKind::PointerBuffer,
+    SectionKind::RecvStatics
+];
+
+/// Byte offsets, from the start of the command, of each section of a serialized command
+///
+/// Sections which are not present in the command have a length of zero, and so share their
+/// offset with the section that follows them.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SectionOffsets {
+    /// The command header, always at the start of the command
+    pub header: usize,
+
+    /// The special header, including its process ID and handles
+    pub special_header: usize,
+
+    /// The send statics
+    pub send_statics: usize,
+
+    /// The send buffers
+    pub send_buffers: usize,
+
+    /// The receive buffers
+    pub recv_buffers: usize,
+
+    /// The exchange buffers
+    pub exch_buffers: usize,
+
+    /// The raw data payload
+    pub raw_data: usize,
+
+    /// The inline buffer, aligned to 16 bytes when present
+    pub inline_buffer: usize,
+
+    /// The pointer buffer receive list entry
+    pub pointer_buffer: usize,
+
+    /// The receive static receive list entries
+    pub recv_statics: usize,
+
+    /// The end of the command, equal to the total number of bytes it consumes
+    pub end: usize
+}
+
+impl SectionOffsets {
+    /// Gets the offset of a section by its kind
+    pub const fn of(self, kind: SectionKind) -> usize {
+        match kind {
+            SectionKind::Header => self.header,
+            SectionKind::SpecialHeader => self.special_header,
+            SectionKind::SendStatics => self.send_statics,
+            SectionKind::SendBuffers => self.send_buffers,
+            SectionKind::RecvBuffers => self.recv_buffers,
+            SectionKind::ExchBuffers => self.exch_buffers,
+            SectionKind::RawData => self.raw_data,
+            SectionKind::InlineBuffer => self.inline_buffer,
+            SectionKind::PointerBuffer => self.pointer_buffer,
+            SectionKind::RecvStatics => self.recv_statics
+        }
+    }
+
+    /// Gets the value of the header's receive list offset field, in words from the start of
+    /// the command
+    ///
+    /// This is zero when the command has no receive list entries, which leaves the field at
+    /// its default.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command;
+    ///
+    /// // Two words of raw data after the header, then two receive statics
+    /// let offsets = command::section_offsets(0, 0, 0, 0, 2, 2, 0, 0, false);
+    /// assert_eq!(offsets.receive_list_offset(), 0x10 / 4);
+    ///
+    /// // Nothing to receive into
+    /// let offsets = command::section_offsets(0, 0, 0, 0, 0, 2, 0, 0, false);
+    /// assert_eq!(offsets.receive_list_offset(), 0);
+    /// ```
+    pub const fn receive_list_offset(self) -> usize {
+        if self.pointer_buffer == self.end {
+            0
+        } else {
+            self.pointer_buffer / core::mem::size_of::<u32>()
+        }
+    }
+}
+
+/// Computes where each section of a command lands once serialized
+///
+/// This is the single source of truth for the layout of a command, used both to size and
+/// to serialize it. Sections are laid out in [`SECTION_ORDER`].
+///
+/// # Arguments
+/// * `send_statics` - The number of statics
+/// * `send_buffers` - The number of send buffers
+/// * `recv_buffers` - The number of receive buffers
+/// * `exch_buffers` - The number of exchange buffers
+/// * `recv_statics` - The number of entries in the receive list
+/// * `raw_len` - The number of 32-bit words in the raw data payload
+/// * `inline_buff_len` - The number of bytes in the inlined receive list buffer
+/// * `special_header_total` - The number of bytes the special header consumes (0 if not present)
+/// * `has_pointer_buffer` - If the command has a pointer buffer for the receive list
+///
+/// # Example
+/// ```
+/// use hipc::command;
+///
+/// // A special header with one copy handle, one send static, two send buffers,
+/// // three words of raw data, and a 16 byte inline buffer
+/// let offsets = command::section_offsets(1, 2, 0, 0, 0, 3, 0x10, 8, false);
+///
+/// assert_eq!(offsets.header, 0x00);
+/// assert_eq!(offsets.special_header, 0x08);
+/// assert_eq!(offsets.send_statics, 0x10);
+/// assert_eq!(offsets.send_buffers, 0x18);
+/// assert_eq!(offsets.recv_buffers, 0x30);
+/// assert_eq!(offsets.exch_buffers, 0x30);
+/// assert_eq!(offsets.raw_data, 0x30);
+/// assert_eq!(offsets.inline_buffer, 0x40);
+/// assert_eq!(offsets.pointer_buffer, 0x50);
+/// assert_eq!(offsets.recv_statics, 0x50);
+/// assert_eq!(offsets.end, 0x50);
+///
+/// // The sections tile the command with no gaps other than the inline buffer padding
+/// let offsets = command::section_offsets(0, 1, 1, 1, 2, 1, 0, 4, false);
+/// assert_eq!(offsets.special_header, offsets.header + 8);
+/// assert_eq!(offsets.send_statics, offsets.special_header + 4);
+/// assert_eq!(offsets.send_buffers, offsets.send_statics);
+/// assert_eq!(offsets.recv_buffers, offsets.send_buffers + 12);
+/// assert_eq!(offsets.exch_buffers, offsets.recv_buffers + 12);
+/// assert_eq!(offsets.raw_data, offsets.exch_buffers + 12);
+/// assert_eq!(offsets.inline_buffer, offsets.raw_data + 4);
+/// assert_eq!(offsets.pointer_buffer, offsets.inline_buffer);
+/// assert_eq!(offsets.recv_statics, offsets.pointer_buffer);
+/// assert_eq!(offsets.end, offsets.recv_statics + 2 * 8);
+/// ```
+///
+/// Both serializers are driven by these offsets. [`HipcCommandBuilder::build`] writes each
+/// section at its offset into an array sized by them, while the runtime paths such as
+/// [`HipcCommandBuilder::build_at`] emit sections and padding back to back. Checking that they
+/// agree for every kind of section guards the layout against either side drifting.
+/// ```
+/// use hipc::{command, header, packed::{BufferDescriptor, ReceiveListEntry, StaticDescriptor}};
+///
+/// macro_rules! check {
+///     ($builder:expr) => {{
+///         let bytes = $builder.build();
+///
+///         let mut out = [0xFFu8; 0x100];
+///         let end = $builder.build_at(&mut out, 0);
+///
+///         assert_eq!(end, bytes.len(), "{}", stringify!($builder));
+///         assert_eq!(&out[..end], &bytes[..], "{}", stringify!($builder));
+///     }};
+/// }
+///
+/// let buffer = BufferDescriptor::new(0x1000, 0x100, 0);
+/// let entry = ReceiveListEntry::new(0x2000, 0x100);
+/// let pid = header::new_builder().with_program_id(0x10);
+/// let handles = header::new_builder().with_copy_handle(1).with_move_handle(2).with_move_handle(3);
+///
+/// check!(command::request_builder());
+/// check!(command::request_builder().with_raw_data([1u32, 2, 3]));
+/// check!(command::request_builder().with_send_static(StaticDescriptor::new(0, 0x10, 0x3000)).with_raw_data([1u32]));
+/// check!(command::request_builder().with_send_buffer(buffer).with_recv_buffer(buffer).with_exch_buffer(buffer));
+///
+/// // Special headers of every size
+/// check!(command::request_builder().with_special_header(pid).with_raw_data([1u32]));
+/// check!(command::request_builder().with_special_header(handles).with_send_buffer(buffer));
+/// check!(command::request_builder().with_special_header(pid.merge(handles)).with_raw_data([1u32, 2]));
+///
+/// // Inline buffers, whose padding depends on everything before them
+/// check!(command::request_builder().with_inline_buffer([0xAAu8; 1]));
+/// check!(command::request_builder().with_raw_data([1u32]).with_inline_buffer([0xAAu8; 3]));
+/// check!(command::request_builder().with_special_header(pid).with_raw_data([1u32, 2]).with_inline_buffer([0xAAu8; 0x10]));
+/// check!(command::request_builder().with_special_header(handles).with_send_buffer(buffer).with_inline_buffer([0xAAu8; 0x11]));
+///
+/// // Pointer buffers and receive statics, which follow the raw data
+/// check!(command::request_builder().with_pointer_buffer(entry));
+/// check!(command::request_builder().with_special_header(handles).with_raw_data([1u32, 2, 3]).with_pointer_buffer(entry));
+/// check!(command::request_builder().with_recv_static(entry).with_recv_static(entry).with_raw_data([1u32]));
+/// check!(command::request_builder().with_special_header(pid).with_send_buffer(buffer).with_recv_static(entry));
+/// ```
+#[allow(clippy::too_many_arguments)]
+pub const fn section_offsets(
+    send_statics: usize,
+    send_buffers: usize,
+    recv_buffers: usize,
+    exch_buffers: usize,
+    recv_statics: usize,
+    raw_len: usize,
+    inline_buff_len: usize,
+    special_header_total: usize,
+    has_pointer_buffer: bool
+) -> SectionOffsets
+{
+    let mut offsets = SectionOffsets {
+        header: 0,
+        special_header: 0,
+        send_statics: 0,
+        send_buffers: 0,
+        recv_buffers: 0,
+        exch_buffers: 0,
+        raw_data: 0,
+        inline_buffer: 0,
+        pointer_buffer: 0,
+        recv_statics: 0,
+        end: 0
+    };
+
+    let mut offset = 0;
+    let mut section = 0;
+    while section < SECTION_ORDER.len() {
+        let len = match SECTION_ORDER[section] {
+            SectionKind::Header => core::mem::size_of::<Header>(),
+            SectionKind::SpecialHeader => special_header_total,
+            SectionKind::SendStatics => core::mem::size_of::<StaticDescriptor>() * send_statics,
+            SectionKind::SendBuffers => core::mem::size_of::<BufferDescriptor>() * send_buffers,
+            SectionKind::RecvBuffers => core::mem::size_of::<BufferDescriptor>() * recv_buffers,
+            SectionKind::ExchBuffers => core::mem::size_of::<BufferDescriptor>() * exch_buffers,
+            SectionKind::RawData => core::mem::size_of::<u32>() * raw_len,
+            SectionKind::InlineBuffer => inline_buff_len,
+            SectionKind::PointerBuffer => core::mem::size_of::<ReceiveListEntry>() * has_pointer_buffer as usize,
+            SectionKind::RecvStatics => core::mem::size_of::<ReceiveListEntry>() * recv_statics
+        };
+
+        // The inline buffer is the only section with an alignment beyond a single word
+        if matches!(SECTION_ORDER[section], SectionKind::InlineBuffer) && len != 0 {
+            offset = helpers::align_up(offset, 16);
+        }
+
+        match SECTION_ORDER[section] {
+            SectionKind::Header => offsets.header = offset,
+            SectionKind::SpecialHeader => offsets.special_header = offset,
+            SectionKind::SendStatics => offsets.send_statics = offset,
+            SectionKind::SendBuffers => offsets.send_buffers = offset,
+            SectionKind::RecvBuffers => offsets.recv_buffers = offset,
+            SectionKind::ExchBuffers => offsets.exch_buffers = offset,
+            SectionKind::RawData => offsets.raw_data = offset,
+            SectionKind::InlineBuffer => offsets.inline_buffer = offset,
+            SectionKind::PointerBuffer => offsets.pointer_buffer = offset,
+            SectionKind::RecvStatics => offsets.recv_statics = offset
+        }
+
+        offset += len;
+        section += 1;
+    }
+
+    offsets.end = offset;
+    offsets
 }
 
 #[doc(hidden)]
@@ -98,15 +861,22 @@ pub mod helpers {
     /// * Panics if multiple of the above parameters are set simultaneously
     #[track_caller]
     const fn panic_on_invalid_recv_list(recv_statics: usize, inline_buff_len: usize, has_pointer_buffer: bool) {
-        if recv_statics != 0 && inline_buff_len != 0 {
-            panic!("Static receivers found with an inline buffer, this combination is illegal");
-        }
-        if recv_statics != 0 && has_pointer_buffer {
-            panic!("Static receivers found with a setting for a pointer buffer, this combination is illegal");
-        }
-        if inline_buff_len != 0 && has_pointer_buffer {
-            panic!("Inline buffer found with a setting for a pointer buffer, this combination is illegal");
-        }
+        const_assert!(
+            recv_statics == 0 || inline_buff_len == 0,
+            "Static receivers found with an inline buffer, this combination is illegal",
+            "receive statics" => recv_statics,
+            "inline buffer length" => inline_buff_len
+        );
+        const_assert!(
+            recv_statics == 0 || !has_pointer_buffer,
+            "Static receivers found with a setting for a pointer buffer, this combination is illegal",
+            "receive statics" => recv_statics
+        );
+        const_assert!(
+            inline_buff_len == 0 || !has_pointer_buffer,
+            "Inline buffer found with a setting for a pointer buffer, this combination is illegal",
+            "inline buffer length" => inline_buff_len
+        );
     }
 
     /// Calculates the consumed space in the command, to ensure that the user does
@@ -142,23 +912,17 @@ pub mod helpers {
         // Check out receive list rq
         panic_on_invalid_recv_list(recv_statics, inline_buff_len, has_pointer_buffer);
 
-        let mut total = core::mem::size_of::<Header>();
-        total += core::mem::size_of::<StaticDescriptor>() * send_statics;
-        total += core::mem::size_of::<BufferDescriptor>() * send_buffers;
-        total += core::mem::size_of::<BufferDescriptor>() * recv_buffers;
-        total += core::mem::size_of::<BufferDescriptor>() * exch_buffers;
-        total += core::mem::size_of::<u32>() * raw_len;
-        total += special_header_total;
-
-        if recv_statics > 0 {
-            total += core::mem::size_of::<ReceiveListEntry>() * recv_statics;
-        } else if inline_buff_len != 0 {
-            total += inline_buff_len;
-        } else if has_pointer_buffer {
-            total += core::mem::size_of::<ReceiveListEntry>();
-        }
-
-        total
+        section_offsets(
+            send_statics,
+            send_buffers,
+            recv_buffers,
+            exch_buffers,
+            recv_statics,
+            raw_len,
+            inline_buff_len,
+            special_header_total,
+            has_pointer_buffer
+        ).end
     }
 
     /// Calculates the consumed space in the command
@@ -194,13 +958,263 @@ pub mod helpers {
             has_pointer_buffer
         );
 
-        if total > MAX_TLS_BUFFER_SIZE {
-            panic!("Size is greater than what the TLS supports!");
-        }
+        const_assert!(
+            total <= MAX_TLS_BUFFER_SIZE,
+            "Size is greater than what the TLS supports!",
+            "size" => total,
+            "max" => MAX_TLS_BUFFER_SIZE
+        );
 
         total
     }
 
+    /// Checks that the descriptors across every section fit within [`MAX_DESCRIPTORS`]
+    ///
+    /// Each section is already limited individually, this catches combinations that are valid
+    /// per section but could never fit together.
+    ///
+    /// # Panicking
+    /// * The combined number of descriptors is greater than [`MAX_DESCRIPTORS`], the panic
+    ///   message lists the count for each section
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::{helpers::check_descriptor_count, MAX_DESCRIPTORS};
+    ///
+    /// // Exactly at the limit
+    /// assert_eq!(MAX_DESCRIPTORS, 31);
+    /// check_descriptor_count(15, 15, 1, 0, 0, 0);
+    /// check_descriptor_count(15, 0, 0, 3, 13, 0);
+    /// ```
+    ///
+    /// ```should_panic
+    /// use hipc::command::helpers::check_descriptor_count;
+    ///
+    /// // One over the limit, even though every section is within its own maximum
+    /// check_descriptor_count(15, 15, 2, 0, 0, 0);
+    /// ```
+    #[track_caller]
+    pub const fn check_descriptor_count(
+        send_statics: usize,
+        send_buffers: usize,
+        recv_buffers: usize,
+        exch_buffers: usize,
+        recv_statics: usize,
+        pointer_buffers: usize
+    ) {
+        let total = send_statics + send_buffers + recv_buffers + exch_buffers + recv_statics + pointer_buffers;
+        const_assert!(
+            total <= MAX_DESCRIPTORS,
+            "Too many descriptors for one command",
+            "send statics" => send_statics,
+            "send buffers" => send_buffers,
+            "receive buffers" => recv_buffers,
+            "exchange buffers" => exch_buffers,
+            "receive statics" => recv_statics,
+            "pointer buffers" => pointer_buffers
+        );
+    }
+
+    /// Rounds `value` up to the next multiple of `align`
+    ///
+    /// # Arguments
+    /// * `value` - The value to round, usually a byte offset or length
+    /// * `align` - The alignment to round to
+    ///
+    /// # Panicking
+    /// * `align` is zero
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::helpers::align_up;
+    ///
+    /// assert_eq!(align_up(8, 16), 16);
+    /// assert_eq!(align_up(16, 16), 16);
+    /// assert_eq!(align_up(17, 16), 32);
+    /// assert_eq!(align_up(0, 16), 0);
+    /// assert_eq!(align_up(5, 4), 8);
+    /// ```
+    #[track_caller]
+    pub const fn align_up(value: usize, align: usize) -> usize {
+        const_assert!(align != 0, "Cannot align to zero!", "value" => value);
+
+        (value + align - 1) / align * align
+    }
+
+    /// Calculates how many more entries `section` can take, limited by both the section's
+    /// maximum count and the space left in the TLS
+    ///
+    /// # Arguments
+    /// * `section` - The section to add entries to
+    /// * The remaining arguments describe the command as it is now, the same as [`check_layout`]
+    ///
+    /// # Returns
+    /// * The number of entries that can still be added, or 0 if the receive list already uses
+    ///   a mode that excludes `section`
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::{helpers, Section};
+    ///
+    /// // An empty command is limited by the count
+    /// assert_eq!(helpers::remaining_capacity(Section::SendBuffers, 0, 0, 0, 0, 0, 0, 0, 0, false), 15);
+    ///
+    /// // With 0xC8 bytes used, only 4 more 12 byte descriptors fit
+    /// assert_eq!(helpers::remaining_capacity(Section::SendBuffers, 0, 0, 0, 0, 0, 0x30, 0, 0, false), 4);
+    ///
+    /// // A pointer buffer excludes receive statics
+    /// assert_eq!(helpers::remaining_capacity(Section::RecvStatics, 0, 0, 0, 0, 0, 0, 0, 0, true), 0);
+    /// ```
+    #[allow(clippy::too_many_arguments)]
+    pub const fn remaining_capacity(
+        section: Section,
+        send_statics: usize,
+        send_buffers: usize,
+        recv_buffers: usize,
+        exch_buffers: usize,
+        recv_statics: usize,
+        raw_len: usize,
+        inline_buff_len: usize,
+        special_header_total: usize,
+        has_pointer_buffer: bool
+    ) -> usize
+    {
+        let current = match section {
+            Section::SendStatics => send_statics,
+            Section::SendBuffers => send_buffers,
+            Section::RecvBuffers => recv_buffers,
+            Section::ExchBuffers => exch_buffers,
+            Section::RecvStatics => recv_statics,
+            Section::SpecialHeader => (special_header_total != 0) as usize,
+            Section::PointerBuffer => has_pointer_buffer as usize
+        };
+
+        // Adding a section can move the alignment of the inline buffer, so rather than dividing
+        // the free space, each candidate count is laid out in full
+        let mut added = 0;
+        while current + added < section.max_count() {
+            let next = added + 1;
+
+            let layout = check_layout(
+                grow(section, Section::SendStatics, send_statics, next),
+                grow(section, Section::SendBuffers, send_buffers, next),
+                grow(section, Section::RecvBuffers, recv_buffers, next),
+                grow(section, Section::ExchBuffers, exch_buffers, next),
+                grow(section, Section::RecvStatics, recv_statics, next),
+                raw_len,
+                inline_buff_len,
+                if matches!(section, Section::SpecialHeader) { core::mem::size_of::<SpecialHeader>() } else { special_header_total },
+                has_pointer_buffer || matches!(section, Section::PointerBuffer)
+            );
+
+            if layout.is_err() {
+                break;
+            }
+
+            added = next;
+        }
+
+        added
+    }
+
+    /// Adds `added` to `count` if `target` is the section being grown
+    const fn grow(section: Section, target: Section, count: usize, added: usize) -> usize {
+        if section as u8 == target as u8 {
+            count + added
+        } else {
+            count
+        }
+    }
+
+    /// Calculates the largest inline buffer that keeps the command within the TLS
+    ///
+    /// The inline buffer starts at the next 16-byte boundary after the rest of the command, and
+    /// room is left at the end of the TLS for the receive list entry the kernel appends when
+    /// describing the inline buffer.
+    ///
+    /// # Arguments
+    /// * `other_section_bytes` - The size of the rest of the command, in bytes (from the header
+    ///   through the end of the raw data)
+    ///
+    /// # Returns
+    /// * The maximum inline buffer length, or 0 if there is no room for one
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, helpers}, header};
+    ///
+    /// // A header and two words of raw data end on a 16 byte boundary already
+    /// let other = helpers::consumed_space(0, 0, 0, 0, 0, 2, 0, 0, false);
+    /// assert_eq!(helpers::max_inline_buffer_len(other), 0x100 - 0x10 - 8);
+    ///
+    /// // A special header with a copy handle pushes the inline buffer to the next boundary
+    /// let special = header::new_builder().with_copy_handle(0xFFFF_8001);
+    /// let other = helpers::consumed_space(0, 0, 0, 0, 0, 2, 0, special.byte_len(), false);
+    /// assert_eq!(other, 0x18);
+    /// assert_eq!(helpers::max_inline_buffer_len(other), 0x100 - 0x20 - 8);
+    ///
+    /// // No room left at all
+    /// assert_eq!(helpers::max_inline_buffer_len(0xF8), 0);
+    /// ```
+    pub const fn max_inline_buffer_len(other_section_bytes: usize) -> usize {
+        let start = align_up(other_section_bytes, 16);
+        let end = MAX_TLS_BUFFER_SIZE - core::mem::size_of::<ReceiveListEntry>();
+
+        end.saturating_sub(start)
+    }
+
+    /// Calculates the number of words before the 16-byte aligned start of the CMIF region
+    ///
+    /// This is the header, special header and all of the descriptors, rounded up to 16 bytes.
+    /// The raw data itself begins directly after the descriptors, so any padding this adds
+    /// is part of the raw data payload.
+    ///
+    /// # Arguments
+    /// * `send_statics` - The number of statics
+    /// * `send_buffers` - The number of read buffers
+    /// * `recv_buffers` - The number of rw buffers
+    /// * `exch_buffers` - The number of exchange buffers
+    /// * `special_header_total` - The size of the special header, in bytes
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::helpers::pre_raw_data_words;
+    ///
+    /// // Just the header, padded out to 16 bytes
+    /// assert_eq!(pre_raw_data_words(0, 0, 0, 0, 0), 4);
+    ///
+    /// // Header + 8 byte special header is already aligned
+    /// assert_eq!(pre_raw_data_words(0, 0, 0, 0, 8), 4);
+    ///
+    /// // Header + one static descriptor + one buffer descriptor = 28 bytes
+    /// assert_eq!(pre_raw_data_words(1, 1, 0, 0, 0), 8);
+    ///
+    /// // Header + special header with a PID and a handle + three buffers = 64 bytes
+    /// assert_eq!(pre_raw_data_words(0, 1, 1, 1, 16), 16);
+    /// ```
+    pub const fn pre_raw_data_words(
+        send_statics: usize,
+        send_buffers: usize,
+        recv_buffers: usize,
+        exch_buffers: usize,
+        special_header_total: usize
+    ) -> usize
+    {
+        let raw_data = section_offsets(
+            send_statics,
+            send_buffers,
+            recv_buffers,
+            exch_buffers,
+            0,
+            0,
+            0,
+            special_header_total,
+            false
+        ).raw_data;
+
+        align_up(raw_data, 16) / core::mem::size_of::<u32>()
+    }
+
     /// Increments a value at compile time, panicking if it exceeds the maximum allowed value
     /// 
     /// # Arguments
@@ -209,16 +1223,250 @@ pub mod helpers {
     /// * `err_msg` - The panic message if incrementing fails
     /// 
     /// # Panicking
-    /// * `current` >= `max`
+    /// * `current` >= `max`, the panic message is followed by both values
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::helpers;
+    ///
+    /// assert_eq!(helpers::safe_increment(2, 3, "Too many send statics!"), 3);
+    ///
+    /// let panic = std::panic::catch_unwind(|| helpers::safe_increment(3, 3, "Too many send statics!")).unwrap_err();
+    /// assert_eq!(panic.downcast_ref::<String>().unwrap(), "Too many send statics! (current: 3, max: 3)");
+    /// ```
+    ///
+    /// The same check fails to compile when it is const evaluated.
+    /// ```compile_fail
+    /// use hipc::command::helpers;
+    ///
+    /// const COUNT: usize = helpers::safe_increment(3, 3, "Too many send statics!");
+    /// ```
     #[track_caller]
     pub const fn safe_increment(current: usize, max: usize, err_msg: &'static str) -> usize {
-        if current >= max {
-            panic!("{}", err_msg);
-        }
+        const_assert!(current < max, err_msg, "current" => current, "max" => max);
 
         current + 1
     }
 
+    /// Adds a count to a value at compile time, panicking if it exceeds the maximum allowed value
+    ///
+    /// # Arguments
+    /// * `current` - The current value
+    /// * `count` - The amount to add
+    /// * `max` - The maximum value
+    /// * `err_msg` - The panic message if adding fails
+    ///
+    /// # Panicking
+    /// * `current + count` > `max`
+    #[track_caller]
+    pub const fn safe_add(current: usize, count: usize, max: usize, err_msg: &'static str) -> usize {
+        const_assert!(current + count <= max, err_msg, "current" => current, "adding" => count, "max" => max);
+
+        current + count
+    }
+
+    /// Counts receive statics being added to a command, checking that the receive list is not
+    /// already used by another mode
+    ///
+    /// # Arguments
+    /// * `current` - The number of receive statics already in the command
+    /// * `count` - The number of receive statics being added
+    /// * `inline_buff_len` - The length of the command's inline buffer
+    /// * `has_pointer_buffer` - If the command has a pointer buffer
+    ///
+    /// # Panicking
+    /// * The command has an inline buffer or a pointer buffer, the message names both builder calls
+    /// * `current + count` > the maximum number of receive statics (13)
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::helpers;
+    ///
+    /// let panic = std::panic::catch_unwind(|| helpers::add_recv_statics(0, 1, 0x10, false)).unwrap_err();
+    /// assert_eq!(
+    ///     panic.downcast_ref::<String>().unwrap(),
+    ///     "Cannot call `with_recv_static` after `with_inline_buffer`, the receive list holds only one of them \
+    ///      (inline buffer length: 16)"
+    /// );
+    ///
+    /// let panic = std::panic::catch_unwind(|| helpers::add_recv_statics(0, 1, 0, true)).unwrap_err();
+    /// assert_eq!(
+    ///     *panic.downcast_ref::<&str>().unwrap(),
+    ///     "Cannot call `with_recv_static` after `with_pointer_buffer`, the receive list holds only one of them"
+    /// );
+    /// ```
+    #[track_caller]
+    pub const fn add_recv_statics(current: usize, count: usize, inline_buff_len: usize, has_pointer_buffer: bool) -> usize {
+        const_assert!(
+            inline_buff_len == 0,
+            "Cannot call `with_recv_static` after `with_inline_buffer`, the receive list holds only one of them",
+            "inline buffer length" => inline_buff_len
+        );
+        const_assert!(
+            !has_pointer_buffer,
+            "Cannot call `with_recv_static` after `with_pointer_buffer`, the receive list holds only one of them"
+        );
+
+        safe_add(current, count, MAX_RECV_STATICS, "Too many recv statics!")
+    }
+
+    /// Counts a pointer buffer being added to a command, checking that the receive list is not
+    /// already used by another mode
+    ///
+    /// # Arguments
+    /// * `current` - The number of pointer buffers already in the command
+    /// * `recv_statics` - The number of receive statics in the command
+    /// * `inline_buff_len` - The length of the command's inline buffer
+    ///
+    /// # Panicking
+    /// * The command has receive statics or an inline buffer, the message names both builder calls
+    /// * The command already has a pointer buffer
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::helpers;
+    ///
+    /// let panic = std::panic::catch_unwind(|| helpers::add_pointer_buffer(0, 2, 0)).unwrap_err();
+    /// assert_eq!(
+    ///     panic.downcast_ref::<String>().unwrap(),
+    ///     "Cannot call `with_pointer_buffer` after `with_recv_static`, the receive list holds only one of them \
+    ///      (receive statics: 2)"
+    /// );
+    /// ```
+    #[track_caller]
+    pub const fn add_pointer_buffer(current: usize, recv_statics: usize, inline_buff_len: usize) -> usize {
+        const_assert!(
+            recv_statics == 0,
+            "Cannot call `with_pointer_buffer` after `with_recv_static`, the receive list holds only one of them",
+            "receive statics" => recv_statics
+        );
+        const_assert!(
+            inline_buff_len == 0,
+            "Cannot call `with_pointer_buffer` after `with_inline_buffer`, the receive list holds only one of them",
+            "inline buffer length" => inline_buff_len
+        );
+
+        safe_increment(current, MAX_POINTER_BUFS, "Too many pointer buffers!")
+    }
+
+    /// Checks that an inline buffer can be added to a command without the receive list already
+    /// being used by another mode
+    ///
+    /// # Arguments
+    /// * `inline_buff_len` - The length of the inline buffer being added
+    /// * `recv_statics` - The number of receive statics in the command
+    /// * `has_pointer_buffer` - If the command has a pointer buffer
+    ///
+    /// # Returns
+    /// * `inline_buff_len`, unchanged
+    ///
+    /// # Panicking
+    /// * The inline buffer is not empty and the command has receive statics or a pointer buffer,
+    ///   the message names both builder calls
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::helpers;
+    ///
+    /// let panic = std::panic::catch_unwind(|| helpers::add_inline_buffer(0x10, 0, true)).unwrap_err();
+    /// assert_eq!(
+    ///     *panic.downcast_ref::<&str>().unwrap(),
+    ///     "Cannot call `with_inline_buffer` after `with_pointer_buffer`, the receive list holds only one of them"
+    /// );
+    /// ```
+    #[track_caller]
+    pub const fn add_inline_buffer(inline_buff_len: usize, recv_statics: usize, has_pointer_buffer: bool) -> usize {
+        const_assert!(
+            inline_buff_len == 0 || recv_statics == 0,
+            "Cannot call `with_inline_buffer` after `with_recv_static`, the receive list holds only one of them",
+            "receive statics" => recv_statics
+        );
+        const_assert!(
+            inline_buff_len == 0 || !has_pointer_buffer,
+            "Cannot call `with_inline_buffer` after `with_pointer_buffer`, the receive list holds only one of them"
+        );
+
+        inline_buff_len
+    }
+
+    /// Checks that raw data is not being set on a command which already has some
+    ///
+    /// # Arguments
+    /// * `current` - The number of raw data words already in the command
+    /// * `new_len` - The number of raw data words being set
+    ///
+    /// # Returns
+    /// * `new_len`, unchanged
+    ///
+    /// # Panicking
+    /// * `current` is nonzero
+    #[track_caller]
+    pub const fn set_raw_data_len(current: usize, new_len: usize) -> usize {
+        const_assert!(
+            current == 0,
+            "Raw data was already set, compose the full payload with raw_data::new_builder or use replace_raw_data to overwrite it"
+        );
+
+        new_len
+    }
+
+    /// Only compiles when both builders have the same shape, used by [`assert_same_command_shape`]
+    ///
+    /// Every const generic must match. The raw data and inline buffer types may differ, as
+    /// long as they are the same length.
+    ///
+    /// [`assert_same_command_shape`]: crate::command::assert_same_command_shape
+    #[allow(clippy::too_many_arguments)]
+    pub const fn same_shape<
+        const SS: usize,
+        const SB: usize,
+        const RB: usize,
+        const EB: usize,
+        const RS: usize,
+        const SH: usize,
+        const PB: usize,
+        const SH_PIDS: usize,
+        const SH_COPY: usize,
+        const SH_MOVE: usize,
+        const SH_TOTAL: usize,
+        const LEN: usize,
+        const INLINE_BUFFER_LEN: usize,
+        const TOTAL: usize,
+        DataA: IntoWords<LEN> + Copy,
+        InlineBufferA: IntoBytes<INLINE_BUFFER_LEN> + Copy,
+        DataB: IntoWords<LEN> + Copy,
+        InlineBufferB: IntoBytes<INLINE_BUFFER_LEN> + Copy
+    >(
+        _: &HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOTAL, LEN, INLINE_BUFFER_LEN, TOTAL, DataA, InlineBufferA>,
+        _: &HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOTAL, LEN, INLINE_BUFFER_LEN, TOTAL, DataB, InlineBufferB>
+    ) {}
+
+    /// Appends an array to another at compile time
+    ///
+    /// # Arguments
+    /// * `current` - The current array
+    /// * `next` - The array to append
+    /// * `fill` - A placeholder value, every instance of which is overwritten
+    ///
+    /// # Returns
+    /// * The extended array
+    pub const fn append_array<T: Copy + Clone, const N: usize, const M: usize, const N2: usize>(current: [T; N], next: [T; M], fill: T) -> [T; N2] {
+        let mut new = [fill; N2];
+        let mut index = 0;
+        while index < N {
+            new[index] = current[index];
+            index += 1;
+        }
+
+        index = 0;
+        while index < M {
+            new[N + index] = next[index];
+            index += 1;
+        }
+
+        new
+    }
+
     /// Pushes a value to an array at compile time, extending its length
     /// 
     /// # Arguments
@@ -272,11 +1520,22 @@ pub mod helpers {
     /// 
     /// # Panicking
     /// * `start + input.len() > base.len()`
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::helpers;
+    ///
+    /// assert_eq!(helpers::byte_array_write([0u8; 4], [1u8, 2], 2), [0, 0, 1, 2]);
+    ///
+    /// let panic = std::panic::catch_unwind(|| helpers::byte_array_write([0u8; 4], [1u8, 2], 3)).unwrap_err();
+    /// assert_eq!(
+    ///     panic.downcast_ref::<String>().unwrap(),
+    ///     "Input data will exceed base! (start: 3, input length: 2, base length: 4)"
+    /// );
+    /// ```
     #[track_caller]
     pub const fn byte_array_write<const N: usize, const N2: usize>(mut base: [u8; N], input: [u8; N2], start: usize) -> [u8; N] {
-        if start + N2 > N {
-            panic!("Input data will exceed base!");
-        }
+        const_assert!(start + N2 <= N, "Input data will exceed base!", "start" => start, "input length" => N2, "base length" => N);
 
         let mut index = 0;
         while index < N2 {
@@ -286,6 +1545,50 @@ pub mod helpers {
 
         base
     }
+
+    /// Checks if two buffers map overlapping address ranges
+    ///
+    /// # Arguments
+    /// * `first` - The first buffer
+    /// * `second` - The second buffer
+    ///
+    /// # Returns
+    /// * `true` if both buffers are non-empty and their `[address, address + size)` ranges intersect
+    pub const fn buffers_overlap(first: BufferDescriptor, second: BufferDescriptor) -> bool {
+        if first.size() == 0 || second.size() == 0 {
+            return false;
+        }
+
+        let first_end = first.address() + first.size() as u64;
+        let second_end = second.address() + second.size() as u64;
+
+        first.address() < second_end && second.address() < first_end
+    }
+
+    /// Writes one section of a command to a byte array at compile time, naming the section
+    /// when it would overrun the array
+    ///
+    /// # Arguments
+    /// * `base` - The current array
+    /// * `input` - The serialized section
+    /// * `start` - The offset the section begins at
+    /// * `err_msg` - The panic message if the section does not fit
+    ///
+    /// # Panicking
+    /// * `start + input.len() > base.len()`
+    ///
+    /// # Example
+    /// ```should_panic
+    /// use hipc::command::helpers;
+    ///
+    /// helpers::section_write([0u8; 8], [1u8; 4], 6, "Raw data overruns the command!");
+    /// ```
+    #[track_caller]
+    pub const fn section_write<const N: usize, const N2: usize>(base: [u8; N], input: [u8; N2], start: usize, err_msg: &'static str) -> [u8; N] {
+        const_assert!(start + N2 <= N, err_msg, "start" => start, "section length" => N2, "command length" => N);
+
+        byte_array_write(base, input, start)
+    }
 }
 
 macro_rules! make_ty {
@@ -466,6 +1769,28 @@ macro_rules! make_ty {
     };
 
     (raw_data => ($T:ty, $new_len:expr)) => {
+        HipcCommandBuilder
+        <
+            SS,
+            SB,
+            RB,
+            EB,
+            RS,
+            SH,
+            PB,
+            SH_PIDS,
+            SH_COPY,
+            SH_MOVE,
+            SH_TOTAL,
+            { $new_len },
+            INLINE_BUFFER_LEN,
+            { helpers::consumed_space(SS, SB, RB, EB, RS, helpers::set_raw_data_len(LEN, $new_len), INLINE_BUFFER_LEN, SH_TOTAL, PB != 0) },
+            $T,
+            InlineBuffer
+        >
+    };
+
+    (raw_data_unchecked => ($T:ty, $new_len:expr)) => {
         HipcCommandBuilder
         <
             SS,
@@ -503,20 +1828,58 @@ macro_rules! make_ty {
             SH_TOTAL,
             LEN,
             { $new_len },
-            { helpers::consumed_space(SS, SB, RB, EB, RS, LEN, $new_len, SH_TOTAL, PB != 0) },
+            { helpers::consumed_space(SS, SB, RB, EB, RS, LEN, helpers::add_inline_buffer($new_len, RS, PB != 0), SH_TOTAL, PB != 0) },
             Data,
             $T
         >
     };
 }
 
-impl
-<
-    const SS: usize,
-    const SB: usize,
-    const RB: usize,
-    const EB: usize,
-    const RS: usize,
+impl make_ty!() {
+    /// Constructs a new, empty command.
+    ///
+    /// This only exists on the empty shape, so that a builder type with sections already in it
+    /// cannot be used to construct one. It is usually easier to call [`new_builder`].
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, HipcCommandBuilder}, CommandType};
+    ///
+    /// let bytes = HipcCommandBuilder::new(CommandType::Request).build();
+    /// assert_eq!(bytes, command::request_builder().build());
+    /// ```
+    ///
+    /// A builder type with a different shape does not have a `new`, since it could never
+    /// return that shape:
+    /// ```compile_fail
+    /// use hipc::{command::RequestWithSendBuffer, CommandType};
+    ///
+    /// let _ = RequestWithSendBuffer::<1>::new(CommandType::Request);
+    /// ```
+    pub const fn new(ty: CommandType) -> Self {
+        HipcCommandBuilder {
+            ty,
+            send_statics: [],
+            send_buffers: [],
+            recv_buffers: [],
+            exch_buffers: [],
+            recv_statics: [],
+            special_hdrs: [],
+            pointer_bufs: [],
+            raw_data: [],
+            inline_buffer: [],
+            context_token: None
+        }
+    }
+}
+
+impl
+<
+    const SS: usize,
+    const SB: usize,
+    const RB: usize,
+    const EB: usize,
+    const RS: usize,
     const SH: usize,
     const PB: usize,
 
@@ -534,25 +1897,6 @@ impl
 >
 HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOTAL, LEN, INLINE_BUFFER_LEN, TOTAL, Data, InlineBuffer>
 {
-    /// Constructs a new, empty command.
-    /// 
-    /// Because this function requires type parameters to be called properly, it is recommended
-    /// to call [`new_builder`] instead.
-    pub const fn new(ty: CommandType) -> make_ty!() {
-        HipcCommandBuilder::<0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 8, [u32; 0], [u8; 0]> {
-            ty,
-            send_statics: [],
-            send_buffers: [],
-            recv_buffers: [],
-            exch_buffers: [],
-            recv_statics: [],
-            special_hdrs: [],
-            pointer_bufs: [],
-            raw_data: [],
-            inline_buffer: []
-        }
-    }
-
     /// Adds an InPointer/"Send Static" to this command (max 15)
     pub const fn with_send_static(self, desc: StaticDescriptor) -> make_ty!(send_static => helpers::safe_increment(SS, MAX_SEND_STATICS, "Too many send statics!")) {
         HipcCommandBuilder {
@@ -565,11 +1909,17 @@ HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOT
             special_hdrs: self.special_hdrs,
             pointer_bufs: self.pointer_bufs,
             raw_data: self.raw_data,
-            inline_buffer: self.inline_buffer
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
         }
     }
 
     /// Adds an InMapAlias/"Send Buffer" to this command (max 15)
+    ///
+    /// The descriptor is written exactly as given, including one with a size of zero. An empty
+    /// descriptor (zero address and zero size) is legal and is how a command passes "no buffer"
+    /// in a slot the service expects to be present, such as an optional input. Use
+    /// [`HipcCommandBuilder::with_send_buffer_checked`] when the buffer is never meant to be empty.
     pub const fn with_send_buffer(self, desc: BufferDescriptor) -> make_ty!(send_buffer => helpers::safe_increment(SB, MAX_SEND_BUFFERS, "Too many send buffers!")) {
         HipcCommandBuilder {
             ty: self.ty,
@@ -581,10 +1931,72 @@ HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOT
             special_hdrs: self.special_hdrs,
             pointer_bufs: self.pointer_bufs,
             raw_data: self.raw_data,
-            inline_buffer: self.inline_buffer
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
         }
     }
 
+    /// Adds an InMapAlias/"Send Buffer" to this command (max 15), rejecting a zero-size descriptor
+    ///
+    /// This catches a forgotten size at build time. For the rare case where an empty buffer is
+    /// intended, use [`HipcCommandBuilder::with_send_buffer`].
+    ///
+    /// # Panicking
+    /// * The descriptor has a size of zero
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, packed::BufferDescriptor};
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_send_buffer_checked(BufferDescriptor::new(0x1000, 0x100, 0))
+    ///     .build();
+    ///
+    /// assert_eq!(bytes, command::request_builder().with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0)).build());
+    ///
+    /// // The permissive method still accepts an empty buffer
+    /// let _ = command::request_builder().with_send_buffer(BufferDescriptor::new(0, 0, 0)).build();
+    ///
+    /// let panic = std::panic::catch_unwind(|| {
+    ///     command::request_builder().with_send_buffer_checked(BufferDescriptor::new(0x1000, 0, 0)).build()
+    /// }).unwrap_err();
+    /// assert_eq!(panic.downcast_ref::<String>().unwrap(), "Send buffer has a size of zero! (address: 4096, size: 0)");
+    /// ```
+    ///
+    /// ```compile_fail
+    /// #![feature(const_trait_impl)]
+    /// use hipc::{command, packed::BufferDescriptor};
+    ///
+    /// const BYTES: [u8; 20] = command::request_builder()
+    ///     .with_send_buffer_checked(BufferDescriptor::new(0x1000, 0, 0))
+    ///     .build();
+    /// ```
+    #[track_caller]
+    pub const fn with_send_buffer_checked(self, desc: BufferDescriptor) -> make_ty!(send_buffer => helpers::safe_increment(SB, MAX_SEND_BUFFERS, "Too many send buffers!")) {
+        const_assert!(desc.size() != 0, "Send buffer has a size of zero!", "address" => desc.address(), "size" => desc.size());
+
+        self.with_send_buffer(desc)
+    }
+
+    /// Adds a send buffer with mode 0 (Normal), see [`BufferDescriptor::normal`]
+    ///
+    /// Use [`HipcCommandBuilder::with_send_buffer`] for any other mode.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, packed::BufferDescriptor};
+    ///
+    /// let shortcut = command::request_builder().with_send_buffer_normal(0x1000, 0x100).build();
+    /// let explicit = command::request_builder().with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0)).build();
+    /// assert_eq!(shortcut, explicit);
+    ///
+    /// // The mode sits in the low bits of the descriptor's last word
+    /// assert_eq!(shortcut[16] & 0x3, 0);
+    /// ```
+    pub const fn with_send_buffer_normal(self, address: u64, size: usize) -> make_ty!(send_buffer => helpers::safe_increment(SB, MAX_SEND_BUFFERS, "Too many send buffers!")) {
+        self.with_send_buffer(BufferDescriptor::normal(address, size))
+    }
+
     /// Adds an OutMapAlias/"Receive Buffer" to this command (max 15)
     pub const fn with_recv_buffer(self, desc: BufferDescriptor) -> make_ty!(recv_buffer => helpers::safe_increment(RB, MAX_RECV_BUFFERS, "Too many recv buffers!")) {
         HipcCommandBuilder {
@@ -597,10 +2009,31 @@ HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOT
             special_hdrs: self.special_hdrs,
             pointer_bufs: self.pointer_bufs,
             raw_data: self.raw_data,
-            inline_buffer: self.inline_buffer
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
         }
     }
 
+    /// Adds a receive buffer with mode 0 (Normal), see [`BufferDescriptor::normal`]
+    ///
+    /// Use [`HipcCommandBuilder::with_recv_buffer`] for any other mode.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, packed::BufferDescriptor};
+    ///
+    /// let shortcut = command::request_builder().with_recv_buffer_normal(0x2000, 0x80).build();
+    /// assert_eq!(shortcut[16] & 0x3, 0);
+    ///
+    /// // NonSecure buffers still go through the explicit path
+    /// let non_secure = command::request_builder().with_recv_buffer(BufferDescriptor::new(0x2000, 0x80, 1)).build();
+    /// assert_eq!(non_secure[16] & 0x3, 1);
+    /// assert_eq!(&shortcut[..16], &non_secure[..16]);
+    /// ```
+    pub const fn with_recv_buffer_normal(self, address: u64, size: usize) -> make_ty!(recv_buffer => helpers::safe_increment(RB, MAX_RECV_BUFFERS, "Too many recv buffers!")) {
+        self.with_recv_buffer(BufferDescriptor::normal(address, size))
+    }
+
     /// Adds an InOutMapAlias/"Exchange Buffer" to this command (max 15)
     pub const fn with_exch_buffer(self, desc: BufferDescriptor) -> make_ty!(exch_buffer => helpers::safe_increment(EB, MAX_EXCH_BUFFERS, "Too many exch buffers!")) {
         HipcCommandBuilder {
@@ -613,12 +2046,59 @@ HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOT
             special_hdrs: self.special_hdrs,
             pointer_bufs: self.pointer_bufs,
             raw_data: self.raw_data,
-            inline_buffer: self.inline_buffer
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
         }
     }
 
     /// Adds an OutPointer/"Receive Static" to this command (max 13)
-    pub const fn with_recv_static(self, desc: ReceiveListEntry) -> make_ty!(recv_static => helpers::safe_increment(RS, MAX_RECV_STATICS, "Too many recv statics!")) {
+    ///
+    /// The header's receive list offset points at the first receive static, in words.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, header, packed::{BufferDescriptor, Header, ReceiveListEntry}};
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_special_header(header::new_builder().with_program_id(0))
+    ///     .with_send_buffer(BufferDescriptor::new(0x1000, 0x10, 0))
+    ///     .with_raw_data([1u32, 2, 3])
+    ///     .with_recv_static(ReceiveListEntry::new(0x2000, 0x100))
+    ///     .with_recv_static(ReceiveListEntry::new(0x3000, 0x200))
+    ///     .build();
+    ///
+    /// let header = Header::from_bytes(bytes[..8].try_into().unwrap());
+    ///
+    /// // Header, special header and process ID, send buffer, raw data
+    /// let list_start = 0x08 + 0x0C + 0x0C + 0x0C;
+    /// assert_eq!(header.receive_list_offset(), list_start / 4);
+    /// assert_eq!(bytes.len(), list_start + 0x10);
+    ///
+    /// let first: [u8; 8] = ReceiveListEntry::new(0x2000, 0x100).into();
+    /// assert_eq!(&bytes[list_start..list_start + 8], &first);
+    /// ```
+    ///
+    /// The receive list holds either receive statics, a pointer buffer, or an inline buffer.
+    /// Adding a second mode to a command fails to compile.
+    ///
+    /// ```compile_fail,E0080
+    /// use hipc::{command, packed::ReceiveListEntry};
+    ///
+    /// // The receive list is already used by a pointer buffer
+    /// let _ = command::request_builder()
+    ///     .with_pointer_buffer(ReceiveListEntry::new(0x1000, 0x100))
+    ///     .with_recv_static(ReceiveListEntry::new(0x2000, 0x100));
+    /// ```
+    ///
+    /// ```compile_fail,E0080
+    /// use hipc::{command, packed::ReceiveListEntry};
+    ///
+    /// // The receive list is already used by an inline buffer
+    /// let _ = command::request_builder()
+    ///     .with_inline_buffer([0u8; 0x10])
+    ///     .with_recv_static(ReceiveListEntry::new(0x2000, 0x100));
+    /// ```
+    pub const fn with_recv_static(self, desc: ReceiveListEntry) -> make_ty!(recv_static => helpers::add_recv_statics(RS, 1, INLINE_BUFFER_LEN, PB != 0)) {
         HipcCommandBuilder {
             ty: self.ty,
             send_statics: self.send_statics,
@@ -629,11 +2109,71 @@ HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOT
             special_hdrs: self.special_hdrs,
             pointer_bufs: self.pointer_bufs,
             raw_data: self.raw_data,
-            inline_buffer: self.inline_buffer
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
+        }
+    }
+
+    /// Adds several OutPointers/"Receive Statics" to this command at once (max 13 in total)
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, packed::ReceiveListEntry};
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_recv_statics([
+    ///         ReceiveListEntry::new(0x1000, 0x10),
+    ///         ReceiveListEntry::new(0x2000, 0x20),
+    ///         ReceiveListEntry::new(0x3000, 0x30)
+    ///     ])
+    ///     .build();
+    ///
+    /// // The receive static mode is the number of entries plus two
+    /// assert_eq!((bytes[5] >> 2) & 0xF, 3 + 2);
+    /// assert_eq!(bytes.len(), 8 + 3 * 8);
+    /// ```
+    pub const fn with_recv_statics<const M: usize>(self, entries: [ReceiveListEntry; M]) -> make_ty!(recv_static => helpers::add_recv_statics(RS, M, INLINE_BUFFER_LEN, PB != 0)) {
+        HipcCommandBuilder {
+            ty: self.ty,
+            send_statics: self.send_statics,
+            send_buffers: self.send_buffers,
+            recv_buffers: self.recv_buffers,
+            exch_buffers: self.exch_buffers,
+            recv_statics: helpers::append_array(self.recv_statics, entries, ReceiveListEntry::new(0, 0)),
+            special_hdrs: self.special_hdrs,
+            pointer_bufs: self.pointer_bufs,
+            raw_data: self.raw_data,
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
         }
     }
 
     /// Adds a special header to this command (max 1)
+    ///
+    /// # Empty Special Headers
+    /// A special header without a process ID or any handles is still serialized, as a single
+    /// zeroed word, and still sets the header's special header bit. Servers that read the
+    /// special header unconditionally (for example, ones that always expect the region to be
+    /// present in a particular command's layout) need this, while every other command should
+    /// leave the special header out entirely.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, header, packed::Header};
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_special_header(header::new_builder())
+    ///     .build();
+    ///
+    /// assert_eq!(bytes.len(), 8 + 4);
+    /// assert_eq!(&bytes[8..], &[0, 0, 0, 0]);
+    ///
+    /// let header = Header::new_raw([
+    ///     u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    ///     u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]])
+    /// ]);
+    /// assert!(header.has_special_header());
+    /// ```
     pub const fn with_special_header<
         const PIDS: usize,
         const CP: usize,
@@ -650,15 +2190,38 @@ HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOT
             recv_buffers: self.recv_buffers,
             exch_buffers: self.exch_buffers,
             recv_statics: self.recv_statics,
+            // `SH` is checked to be 0 by the return type, so there is never an existing header to keep
             special_hdrs: helpers::push_array([], header),
             pointer_bufs: self.pointer_bufs,
             raw_data: self.raw_data,
-            inline_buffer: self.inline_buffer
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
         }
     }
 
     /// Adds a pointer buffer to this command (max 1)
-    pub const fn with_pointer_buffer(self, desc: ReceiveListEntry) -> make_ty!(pointer_buffer => helpers::safe_increment(PB, MAX_POINTER_BUFS, "Too many pointer buffers!")) {
+    ///
+    /// The receive list holds either receive statics, a pointer buffer, or an inline buffer.
+    /// Adding a second mode to a command fails to compile.
+    ///
+    /// ```compile_fail,E0080
+    /// use hipc::{command, packed::ReceiveListEntry};
+    ///
+    /// // The receive list is already used by receive statics
+    /// let _ = command::request_builder()
+    ///     .with_recv_static(ReceiveListEntry::new(0x1000, 0x100))
+    ///     .with_pointer_buffer(ReceiveListEntry::new(0x2000, 0x100));
+    /// ```
+    ///
+    /// ```compile_fail,E0080
+    /// use hipc::{command, packed::ReceiveListEntry};
+    ///
+    /// // The receive list is already used by an inline buffer
+    /// let _ = command::request_builder()
+    ///     .with_inline_buffer([0u8; 0x10])
+    ///     .with_pointer_buffer(ReceiveListEntry::new(0x2000, 0x100));
+    /// ```
+    pub const fn with_pointer_buffer(self, desc: ReceiveListEntry) -> make_ty!(pointer_buffer => helpers::add_pointer_buffer(PB, RS, INLINE_BUFFER_LEN)) {
         HipcCommandBuilder {
             ty: self.ty,
             send_statics: self.send_statics,
@@ -669,11 +2232,49 @@ HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOT
             special_hdrs: self.special_hdrs,
             pointer_bufs: helpers::push_array(self.pointer_bufs, desc),
             raw_data: self.raw_data,
-            inline_buffer: self.inline_buffer
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
         }
     }
 
+    /// Adds a pointer buffer to this command from its address and size (max 1)
+    ///
+    /// The size should be the pointer buffer size reported by the server through the
+    /// `QueryPointerBufferSize` control command, as the server copies the command's send statics
+    /// into this buffer.
+    ///
+    /// # Arguments
+    /// * `address` - The address of the pointer buffer
+    /// * `size` - The size of the pointer buffer, in bytes
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command;
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_pointer_buffer_sized(0x1234_5000, 0x500)
+    ///     .build();
+    ///
+    /// // The size lives in the upper half of the entry's second word
+    /// assert_eq!(u16::from_le_bytes([bytes[14], bytes[15]]), 0x500);
+    /// ```
+    pub const fn with_pointer_buffer_sized(self, address: u64, size: usize) -> make_ty!(pointer_buffer => helpers::add_pointer_buffer(PB, RS, INLINE_BUFFER_LEN)) {
+        self.with_pointer_buffer(PointerBufferEntry::new(address, size).into())
+    }
+
     /// Adds the raw data payload to this command
+    ///
+    /// Raw data can only be set once. Compose a payload out of several parts with
+    /// [`raw_data::new_builder`](crate::raw_data::new_builder), or use
+    /// [`HipcCommandBuilder::replace_raw_data`] to deliberately overwrite it.
+    ///
+    /// ```compile_fail
+    /// use hipc::command;
+    ///
+    /// let _ = command::request_builder()
+    ///     .with_raw_data([1u32])
+    ///     .with_raw_data([2u32]);
+    /// ```
     pub const fn with_raw_data<const N: usize, T: IntoWords<N> + Copy>(self, data: T) -> make_ty!(raw_data => (T, N))
     {
         HipcCommandBuilder {
@@ -686,11 +2287,344 @@ HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOT
             special_hdrs: self.special_hdrs,
             pointer_bufs: self.pointer_bufs,
             raw_data: data,
-            inline_buffer: self.inline_buffer
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
+        }
+    }
+
+    /// Replaces the raw data payload of this command, discarding any that was already set
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command;
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_raw_data([1u32, 2])
+    ///     .replace_raw_data([3u32])
+    ///     .build();
+    ///
+    /// assert_eq!(bytes, command::request_builder().with_raw_data([3u32]).build());
+    /// ```
+    pub const fn replace_raw_data<const N: usize, T: IntoWords<N> + Copy>(self, data: T) -> make_ty!(raw_data_unchecked => (T, N))
+    {
+        HipcCommandBuilder {
+            ty: self.ty,
+            send_statics: self.send_statics,
+            send_buffers: self.send_buffers,
+            recv_buffers: self.recv_buffers,
+            exch_buffers: self.exch_buffers,
+            recv_statics: self.recv_statics,
+            special_hdrs: self.special_hdrs,
+            pointer_bufs: self.pointer_bufs,
+            raw_data: data,
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
+        }
+    }
+
+    /// Reserves `N` words of raw data to be filled in later
+    ///
+    /// The reserved region is accounted for in the command's size and is serialized as zeroes.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command;
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_raw_data_reserved::<4>()
+    ///     .build();
+    ///
+    /// assert_eq!(bytes.len(), 8 + 4 * 4);
+    /// assert!(bytes[8..].iter().all(|byte| *byte == 0));
+    /// ```
+    pub const fn with_raw_data_reserved<const N: usize>(self) -> make_ty!(raw_data => ([u32; N], N)) {
+        self.with_raw_data([0u32; N])
+    }
+
+    /// Adds only the first `USED` words of `data` as the raw data payload to this command
+    ///
+    /// This is an escape hatch for sending a prefix of a larger staging buffer. The server
+    /// only sees the first `USED` words, and the header's raw data length is set to `USED`.
+    ///
+    /// # Panicking
+    /// * `USED > FULL`
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command;
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_raw_data_truncated::<4, 2>([1, 2, 3, 4])
+    ///     .build();
+    ///
+    /// assert_eq!(bytes.len(), 8 + 2 * 4);
+    /// assert_eq!(bytes[4], 2);
+    /// assert_eq!(&bytes[8..], &[1, 0, 0, 0, 2, 0, 0, 0]);
+    /// ```
+    #[track_caller]
+    pub const fn with_raw_data_truncated<const FULL: usize, const USED: usize>(self, data: [u32; FULL]) -> make_ty!(raw_data => ([u32; USED], USED)) {
+        const_assert!(USED <= FULL, "Cannot send more raw data words than were provided!", "used" => USED, "provided" => FULL);
+
+        let mut used = [0u32; USED];
+        let mut index = 0;
+        while index < USED {
+            used[index] = data[index];
+            index += 1;
+        }
+
+        self.with_raw_data(used)
+    }
+
+    /// Attaches a context token to this command, turning it into its context variant
+    ///
+    /// The token is carried in the CMIF request header, as described by
+    /// [`cmif::CONTEXT_VERSION`](crate::cmif::CONTEXT_VERSION), and is written there when the
+    /// command is built, so the raw data holding that header can be added before or after the
+    /// token. A [`CommandType::Request`] becomes a [`CommandType::RequestWithContext`] and a
+    /// [`CommandType::Control`] becomes a [`CommandType::ControlWithContext`].
+    ///
+    /// # Panicking
+    /// * The command is not a request or control command
+    ///
+    /// Building the command also panics if its raw data does not contain a CMIF request header.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{cmif::{self, CmifMode}, command::{self, parse::ParsedCommand}, CommandType};
+    ///
+    /// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+    ///
+    /// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 1, 0)] =
+    ///     cmif::request_words(OFFSET, CmifMode::Session, 42, [7], &[]);
+    /// let bytes = command::request_builder().with_raw_data(words).with_context_token(0x1234).build();
+    ///
+    /// assert_eq!(bytes, [
+    ///     0x06, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00, // Request with context, 7 words of raw data
+    ///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Padding to the CMIF header
+    ///     b'S', b'F', b'C', b'I', 0x01, 0x00, 0x00, 0x00, // CMIF magic, context version
+    ///     0x2A, 0x00, 0x00, 0x00, 0x34, 0x12, 0x00, 0x00, // Command ID 42, token
+    ///     0x07, 0x00, 0x00, 0x00                          // Argument
+    /// ]);
+    ///
+    /// let command = ParsedCommand::try_from(&bytes[..]).unwrap();
+    /// assert_eq!(command.context_token(), Some(0x1234));
+    ///
+    /// // The token can come first
+    /// let first = command::request_builder().with_context_token(0x1234).with_raw_data(words).build();
+    /// assert_eq!(first, bytes);
+    ///
+    /// let panic = std::panic::catch_unwind(|| command::new_builder(CommandType::Close).with_context_token(0x1234).build()).unwrap_err();
+    /// assert_eq!(
+    ///     panic.downcast_ref::<String>().unwrap(),
+    ///     "Only request and control commands can carry a context token! (command type: 2)"
+    /// );
+    /// ```
+    #[track_caller]
+    pub const fn with_context_token(self, token: u32) -> Self {
+        const_assert!(
+            matches!(
+                self.ty,
+                CommandType::Request | CommandType::RequestWithContext | CommandType::Control | CommandType::ControlWithContext
+            ),
+            "Only request and control commands can carry a context token!",
+            "command type" => self.ty as u16
+        );
+
+        let ty = match self.ty {
+            CommandType::Request | CommandType::RequestWithContext => CommandType::RequestWithContext,
+            _ => CommandType::ControlWithContext
+        };
+
+        HipcCommandBuilder {
+            ty,
+            send_statics: self.send_statics,
+            send_buffers: self.send_buffers,
+            recv_buffers: self.recv_buffers,
+            exch_buffers: self.exch_buffers,
+            recv_statics: self.recv_statics,
+            special_hdrs: self.special_hdrs,
+            pointer_bufs: self.pointer_bufs,
+            raw_data: self.raw_data,
+            inline_buffer: self.inline_buffer,
+            context_token: Some(token)
+        }
+    }
+
+    /// Gets the raw data as it is serialized, with the context token written into its CMIF
+    /// request header
+    #[track_caller]
+    const fn raw_words(&self, raw_data_offset: usize) -> [u32; LEN]
+    where
+        Data: ~const IntoWords<LEN>
+    {
+        let words: [u32; LEN] = self.raw_data.into();
+        match self.context_token {
+            Some(token) => crate::cmif::with_context_token(words, raw_data_offset, token),
+            None => words
+        }
+    }
+
+    /// Checks the command's context token against its type with [`check_context_token`]
+    ///
+    /// A token given to [`HipcCommandBuilder::with_context_token`] is first checked to have a
+    /// CMIF request header to be written into.
+    const fn token_check(&self) -> Result<(), BuilderError>
+    where
+        Data: ~const IntoWords<LEN>
+    {
+        let raw_data_offset = section_offsets(SS, SB, RB, EB, RS, LEN, INLINE_BUFFER_LEN, SH_TOTAL, PB != 0).raw_data;
+        let words: [u32; LEN] = self.raw_data.into();
+
+        match self.context_token {
+            Some(_) if crate::cmif::request_header_word(&words, raw_data_offset).is_none() => {
+                Err(BuilderError::NoContextTokenHeader)
+            },
+            _ => check_context_token(self.ty, &self.raw_words(raw_data_offset), raw_data_offset)
+        }
+    }
+
+    /// Panics if the command's context token does not match its type
+    #[track_caller]
+    const fn assert_token(&self)
+    where
+        Data: ~const IntoWords<LEN>
+    {
+        match self.token_check() {
+            Ok(()) => {},
+            Err(BuilderError::MissingContextToken) => panic!("Context commands must carry a context token!"),
+            Err(BuilderError::UnexpectedContextToken) => panic!("Only context commands can carry a context token!"),
+            Err(BuilderError::NoContextTokenHeader) => {
+                panic!("The raw data has no CMIF request header to hold the context token!")
+            },
+            Err(_) => panic!("Only context token errors are checked here")
+        }
+    }
+
+    /// Targets this command at an object within a domain by inserting a domain header in front
+    /// of its CMIF request
+    ///
+    /// The raw data must already hold a session CMIF request, or only the padding before the
+    /// CMIF region for [`DomainCommandKind::Close`]. See
+    /// [`cmif::with_domain_header`](crate::cmif::with_domain_header) for how the
+    /// header is placed.
+    ///
+    /// # Panicking
+    /// * The raw data does not reach the start of the CMIF region
+    /// * `kind` is [`DomainCommandKind::Close`] and the raw data holds CMIF data
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{
+    ///     cmif::{self, CmifMode},
+    ///     command::{self, parse::ParsedCommand},
+    ///     domain::{DomainCommandKind, DomainRequestHeader}
+    /// };
+    ///
+    /// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+    ///
+    /// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 1, 0)] =
+    ///     cmif::request_words(OFFSET, CmifMode::Session, 42, [7], &[]);
+    /// let bytes = command::request_builder()
+    ///     .with_raw_data(words)
+    ///     .with_domain_request(0x10, DomainCommandKind::SendMessage)
+    ///     .build();
+    ///
+    /// let command = ParsedCommand::new(&bytes).unwrap();
+    /// assert_eq!(command.header().raw_data_len(), 2 + 4 + 4 + 1);
+    ///
+    /// let layout = cmif::layout(0, 0, 0, 0, 0, true);
+    /// let domain_header = layout.domain_header.unwrap();
+    /// let header = DomainRequestHeader::from_bytes(bytes[domain_header..layout.header].try_into().unwrap());
+    /// assert_eq!(header.kind(), DomainCommandKind::SendMessage as u8);
+    /// assert_eq!(header.object_id(), 0x10);
+    /// assert_eq!(header.data_len(), cmif::CMIF_HEADER_SIZE + 4);
+    /// assert_eq!(cmif::request_command_id(&bytes[layout.raw_data..], layout.raw_data), Ok(42));
+    ///
+    /// // Closing the object sends only the domain header
+    /// let bytes = command::request_builder()
+    ///     .with_raw_data([0u32; 2])
+    ///     .with_domain_request(0x10, DomainCommandKind::Close)
+    ///     .build();
+    /// assert_eq!(&bytes[domain_header..], &<[u8; 16]>::from(DomainRequestHeader::new(DomainCommandKind::Close, 0, 0, 0x10)));
+    /// ```
+    #[track_caller]
+    pub const fn with_domain_request(self, object_id: u32, kind: DomainCommandKind) -> make_ty!(raw_data_unchecked => ([u32; LEN + 4], LEN + 4))
+    where
+        Data: ~const IntoWords<LEN>
+    {
+        let raw_data_offset = section_offsets(SS, SB, RB, EB, RS, LEN, INLINE_BUFFER_LEN, SH_TOTAL, PB != 0).raw_data;
+        let words: [u32; LEN] = self.raw_data.into();
+
+        HipcCommandBuilder {
+            ty: self.ty,
+            send_statics: self.send_statics,
+            send_buffers: self.send_buffers,
+            recv_buffers: self.recv_buffers,
+            exch_buffers: self.exch_buffers,
+            recv_statics: self.recv_statics,
+            special_hdrs: self.special_hdrs,
+            pointer_bufs: self.pointer_bufs,
+            raw_data: crate::cmif::with_domain_header(words, raw_data_offset, kind, object_id),
+            inline_buffer: self.inline_buffer,
+            context_token: self.context_token
         }
     }
 
     /// Adds an inlined buffer to this command (max 1)
+    ///
+    /// The inline buffer always starts on a 16-byte boundary, with zeroed padding between it
+    /// and the raw data.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, header, packed::{BufferDescriptor, StaticDescriptor}};
+    ///
+    /// // 8 bytes of header, 8 of send static, 24 of buffers, and 4 of raw data end at 0x2C
+    /// let bytes = command::request_builder()
+    ///     .with_send_static(StaticDescriptor::new(0, 0x10, 0x1000))
+    ///     .with_send_buffer(BufferDescriptor::new(0x2000, 0x100, 0))
+    ///     .with_recv_buffer(BufferDescriptor::new(0x3000, 0x100, 0))
+    ///     .with_raw_data([1u32])
+    ///     .with_inline_buffer([0xAAu8; 0x10])
+    ///     .build();
+    ///
+    /// let start = bytes.len() - 0x10;
+    /// assert_eq!(start, 0x30);
+    /// assert_eq!(start % 0x10, 0);
+    /// assert_eq!(&bytes[0x2C..start], &[0; 4]);
+    ///
+    /// // The payload is copied verbatim behind a special header and raw data
+    /// let payload = [0x11u8, 0x22, 0x33, 0x44, 0x55];
+    /// let bytes = command::request_builder()
+    ///     .with_special_header(header::new_builder().with_copy_handle(0xFFFF_8001))
+    ///     .with_raw_data([1u32, 2])
+    ///     .with_inline_buffer(payload)
+    ///     .build();
+    ///
+    /// assert_eq!(bytes.len(), 0x20 + payload.len());
+    /// assert_eq!(&bytes[0x20..], &payload);
+    /// ```
+    ///
+    /// The receive list holds either receive statics, a pointer buffer, or an inline buffer.
+    /// Adding a second mode to a command fails to compile.
+    ///
+    /// ```compile_fail,E0080
+    /// use hipc::{command, packed::ReceiveListEntry};
+    ///
+    /// // The receive list is already used by receive statics
+    /// let _ = command::request_builder()
+    ///     .with_recv_static(ReceiveListEntry::new(0x1000, 0x100))
+    ///     .with_inline_buffer([0u8; 0x10]);
+    /// ```
+    ///
+    /// ```compile_fail,E0080
+    /// use hipc::{command, packed::ReceiveListEntry};
+    ///
+    /// // The receive list is already used by a pointer buffer
+    /// let _ = command::request_builder()
+    ///     .with_pointer_buffer(ReceiveListEntry::new(0x1000, 0x100))
+    ///     .with_inline_buffer([0u8; 0x10]);
+    /// ```
     pub const fn with_inline_buffer<const N: usize, T: IntoBytes<N> + Copy>(self, data: T) -> make_ty!(inline_buffer => (T, N))
     where
         [u8; N]: From<T>
@@ -705,18 +2639,487 @@ HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOT
             special_hdrs: self.special_hdrs,
             pointer_bufs: self.pointer_bufs,
             raw_data: self.raw_data,
-            inline_buffer: data
+            inline_buffer: data,
+            context_token: self.context_token
+        }
+    }
+
+    /// The number of send statics in the command
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, header, packed::{BufferDescriptor, ReceiveListEntry, StaticDescriptor}};
+    ///
+    /// let cmd = command::request_builder()
+    ///     .with_send_static(StaticDescriptor::new(0, 0x10, 0x1000))
+    ///     .with_send_buffer(BufferDescriptor::new(0x2000, 0x100, 0))
+    ///     .with_send_buffer(BufferDescriptor::new(0x3000, 0x100, 0))
+    ///     .with_recv_buffer(BufferDescriptor::new(0x4000, 0x100, 0))
+    ///     .with_exch_buffer(BufferDescriptor::new(0x5000, 0x100, 0))
+    ///     .with_special_header(header::new_builder().with_program_id(0).with_copy_handle(1).with_move_handle(2))
+    ///     .with_recv_static(ReceiveListEntry::new(0x6000, 0x100))
+    ///     .with_raw_data([1u32, 2, 3]);
+    ///
+    /// assert_eq!(cmd.num_send_statics(), 1);
+    /// assert_eq!(cmd.num_send_buffers(), 2);
+    /// assert_eq!(cmd.num_recv_buffers(), 1);
+    /// assert_eq!(cmd.num_exch_buffers(), 1);
+    /// assert_eq!(cmd.num_recv_statics(), 1);
+    /// assert!(cmd.has_special_header());
+    /// assert!(!cmd.has_pointer_buffer());
+    /// assert_eq!(cmd.num_pids(), 1);
+    /// assert_eq!(cmd.num_copy(), 1);
+    /// assert_eq!(cmd.num_move(), 1);
+    /// assert_eq!(cmd.special_header_bytes(), 20);
+    /// assert_eq!(cmd.raw_data_words(), 3);
+    /// assert_eq!(cmd.inline_buffer_len(), 0);
+    /// assert_eq!(cmd.total_bytes(), cmd.build().len());
+    /// ```
+    pub const fn num_send_statics(&self) -> usize {
+        SS
+    }
+
+    /// The number of send buffers in the command
+    pub const fn num_send_buffers(&self) -> usize {
+        SB
+    }
+
+    /// The number of receive buffers in the command
+    pub const fn num_recv_buffers(&self) -> usize {
+        RB
+    }
+
+    /// The number of exchange buffers in the command
+    pub const fn num_exch_buffers(&self) -> usize {
+        EB
+    }
+
+    /// The number of entries in the command's receive list, not counting a pointer buffer
+    pub const fn num_recv_statics(&self) -> usize {
+        RS
+    }
+
+    /// If the command has a special header
+    pub const fn has_special_header(&self) -> bool {
+        SH != 0
+    }
+
+    /// If the command has a pointer buffer
+    pub const fn has_pointer_buffer(&self) -> bool {
+        PB != 0
+    }
+
+    /// The number of process IDs in the command's special header
+    pub const fn num_pids(&self) -> usize {
+        SH_PIDS
+    }
+
+    /// The number of copy handles in the command's special header
+    pub const fn num_copy(&self) -> usize {
+        SH_COPY
+    }
+
+    /// The number of move handles in the command's special header
+    pub const fn num_move(&self) -> usize {
+        SH_MOVE
+    }
+
+    /// The size of the command's special header, in bytes
+    pub const fn special_header_bytes(&self) -> usize {
+        SH_TOTAL
+    }
+
+    /// The number of 32-bit words in the command's raw data
+    pub const fn raw_data_words(&self) -> usize {
+        LEN
+    }
+
+    /// The size of the command's inline buffer, in bytes
+    pub const fn inline_buffer_len(&self) -> usize {
+        INLINE_BUFFER_LEN
+    }
+
+    /// The size of the serialized command, in bytes
+    pub const fn total_bytes(&self) -> usize {
+        TOTAL
+    }
+
+    /// How many more send statics can be added, see [`helpers::remaining_capacity`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command;
+    ///
+    /// // Limited by the count
+    /// assert_eq!(command::request_builder().remaining_send_statics(), 15);
+    ///
+    /// // 0xC8 bytes used leaves room for 7 more 8 byte descriptors
+    /// assert_eq!(command::request_builder().with_raw_data([0u32; 0x30]).remaining_send_statics(), 7);
+    /// ```
+    pub const fn remaining_send_statics(&self) -> usize {
+        helpers::remaining_capacity(Section::SendStatics, SS, SB, RB, EB, RS, LEN, INLINE_BUFFER_LEN, SH_TOTAL, PB != 0)
+    }
+
+    /// How many more send buffers can be added, see [`helpers::remaining_capacity`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, packed::BufferDescriptor};
+    ///
+    /// let cmd = command::request_builder().with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0));
+    /// assert_eq!(cmd.remaining_send_buffers(), 14);
+    ///
+    /// let cmd = command::request_builder().with_raw_data([0u32; 0x30]);
+    /// assert_eq!(cmd.remaining_send_buffers(), 4);
+    /// ```
+    pub const fn remaining_send_buffers(&self) -> usize {
+        helpers::remaining_capacity(Section::SendBuffers, SS, SB, RB, EB, RS, LEN, INLINE_BUFFER_LEN, SH_TOTAL, PB != 0)
+    }
+
+    /// How many more receive buffers can be added, see [`helpers::remaining_capacity`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, header};
+    ///
+    /// // 15 more buffers would take 180 bytes, but only 0x100 - 0xD4 = 44 are left
+    /// let cmd = command::request_builder()
+    ///     .with_special_header(header::new_builder().with_program_id(0))
+    ///     .with_raw_data([0u32; 0x30]);
+    /// assert_eq!(cmd.remaining_recv_buffers(), 3);
+    /// ```
+    pub const fn remaining_recv_buffers(&self) -> usize {
+        helpers::remaining_capacity(Section::RecvBuffers, SS, SB, RB, EB, RS, LEN, INLINE_BUFFER_LEN, SH_TOTAL, PB != 0)
+    }
+
+    /// How many more exchange buffers can be added, see [`helpers::remaining_capacity`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command;
+    ///
+    /// assert_eq!(command::request_builder().remaining_exch_buffers(), 15);
+    /// ```
+    pub const fn remaining_exch_buffers(&self) -> usize {
+        helpers::remaining_capacity(Section::ExchBuffers, SS, SB, RB, EB, RS, LEN, INLINE_BUFFER_LEN, SH_TOTAL, PB != 0)
+    }
+
+    /// How many more receive list entries can be added, see [`helpers::remaining_capacity`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command;
+    ///
+    /// assert_eq!(command::request_builder().remaining_recv_statics(), 13);
+    ///
+    /// // An inline buffer excludes a receive list
+    /// assert_eq!(command::request_builder().with_inline_buffer([0u8; 0x10]).remaining_recv_statics(), 0);
+    /// ```
+    pub const fn remaining_recv_statics(&self) -> usize {
+        helpers::remaining_capacity(Section::RecvStatics, SS, SB, RB, EB, RS, LEN, INLINE_BUFFER_LEN, SH_TOTAL, PB != 0)
+    }
+
+    /// If the command carries nothing but its header
+    ///
+    /// Such a command is still valid. It serializes to just the 8 byte header, and is how
+    /// some control commands (and [`CLOSE_COMMAND`]) are sent.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, packed::Header, replay::RawCommand, CommandType};
+    ///
+    /// let cmd = command::new_builder(CommandType::Control);
+    /// assert!(cmd.is_empty());
+    /// assert!(!command::request_builder().with_raw_data([0u32]).is_empty());
+    ///
+    /// let bytes = cmd.build();
+    /// assert_eq!(bytes.len(), 8);
+    ///
+    /// let raw = RawCommand::from_bytes(&bytes).unwrap();
+    /// assert!(raw.body.is_empty());
+    ///
+    /// let header = Header::new_raw(raw.header);
+    /// assert_eq!(header.ty(), CommandType::Control as u16);
+    /// assert!(header == Header::new(CommandType::Control as u16, 0, 0, 0, 0, 0, 0, 0, false));
+    /// ```
+    pub const fn is_empty(&self) -> bool {
+        SS == 0
+            && SB == 0
+            && RB == 0
+            && EB == 0
+            && RS == 0
+            && SH == 0
+            && PB == 0
+            && LEN == 0
+            && INLINE_BUFFER_LEN == 0
+    }
+
+    /// Gets one of the command's buffer descriptors, indexing over the send, receive,
+    /// and exchange buffers in that order
+    const fn buffer_at(&self, index: usize) -> BufferDescriptor {
+        if index < SB {
+            self.send_buffers[index]
+        } else if index < SB + RB {
+            self.recv_buffers[index - SB]
+        } else {
+            self.exch_buffers[index - SB - RB]
+        }
+    }
+
+    /// Checks every buffer descriptor in the command with [`BufferDescriptor::validate`], and
+    /// its context token with [`check_context_token`]
+    ///
+    /// # Failures
+    /// * Any send, receive, or exchange buffer fails validation
+    /// * The command is a context command without a context token
+    /// * The command carries a context token without being a context command
+    /// * The command was given a context token but has no CMIF request header to hold it
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, ValidationError}, packed::BufferDescriptor, CommandType};
+    ///
+    /// let cmd = command::request_builder().with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0));
+    /// assert_eq!(cmd.validate(), Ok(()));
+    ///
+    /// // A context token needs a CMIF request header in the raw data to be written into
+    /// let cmd = command::request_with_context_builder(0x1234).with_raw_data([1u32, 2]);
+    /// assert_eq!(cmd.validate(), Err(ValidationError::NoContextTokenHeader));
+    ///
+    /// let cmd = command::new_builder(CommandType::ControlWithContext).with_raw_data([1u32, 2]);
+    /// assert_eq!(cmd.validate(), Err(ValidationError::MissingContextToken));
+    /// ```
+    pub const fn validate(&self) -> Result<(), ValidationError>
+    where
+        Data: ~const IntoWords<LEN>
+    {
+        let mut index = 0;
+        while index < SB + RB + EB {
+            if let Err(err) = self.buffer_at(index).validate() {
+                return Err(ValidationError::InvalidBuffer(err));
+            }
+            index += 1;
+        }
+
+        match self.token_check() {
+            Ok(()) => Ok(()),
+            Err(BuilderError::MissingContextToken) => Err(ValidationError::MissingContextToken),
+            Err(BuilderError::UnexpectedContextToken) => Err(ValidationError::UnexpectedContextToken),
+            Err(BuilderError::NoContextTokenHeader) => Err(ValidationError::NoContextTokenHeader),
+            Err(_) => panic!("Only context token errors are checked here")
+        }
+    }
+
+    /// Performs the same checks as [`HipcCommandBuilder::validate`], additionally checking that
+    /// no two buffers in the command map overlapping address ranges
+    ///
+    /// Empty buffers never overlap anything.
+    ///
+    /// # Failures
+    /// * The same situations as [`HipcCommandBuilder::validate`]
+    /// * Two send, receive, or exchange buffers overlap
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, ValidationError}, packed::BufferDescriptor};
+    ///
+    /// let cmd = command::request_builder()
+    ///     .with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+    ///     .with_recv_buffer(BufferDescriptor::new(0x2000, 0x100, 0))
+    ///     .with_recv_buffer(BufferDescriptor::new(0x2080, 0x100, 0));
+    ///
+    /// assert_eq!(cmd.validate(), Ok(()));
+    /// assert_eq!(cmd.validate_strict(), Err(ValidationError::OverlappingBuffers));
+    /// ```
+    pub const fn validate_strict(&self) -> Result<(), ValidationError>
+    where
+        Data: ~const IntoWords<LEN>
+    {
+        if let Err(err) = self.validate() {
+            return Err(err);
+        }
+
+        let mut first = 0;
+        while first < SB + RB + EB {
+            let mut second = first + 1;
+            while second < SB + RB + EB {
+                if helpers::buffers_overlap(self.buffer_at(first), self.buffer_at(second)) {
+                    return Err(ValidationError::OverlappingBuffers);
+                }
+                second += 1;
+            }
+            first += 1;
+        }
+
+        Ok(())
+    }
+
+    /// Builds the command into a sequence of bytes, returning an error instead of panicking
+    /// when the command's shape is not allowed
+    ///
+    /// Conflicting receive list modes are already rejected while the builder's type is
+    /// computed, so in practice the errors this returns are a command too large for the TLS
+    /// and a context token that does not match the command's type.
+    ///
+    /// # Failures
+    /// * The same situations as [`check_layout`]
+    /// * The same situations as [`check_context_token`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::{self, BuilderError};
+    ///
+    /// let bytes = command::request_builder().with_raw_data([1u32, 2]).try_build();
+    /// assert_eq!(bytes, Ok(command::request_builder().with_raw_data([1u32, 2]).build()));
+    ///
+    /// let too_large = command::request_builder().with_raw_data([0u32; 0x40]).try_build();
+    /// assert_eq!(too_large, Err(BuilderError::TlsOverflow { needed: 8 + 0x100, max: 0x100 }));
+    ///
+    /// // A context token with no CMIF request header to carry it
+    /// let missing = command::request_with_context_builder(0x1234).with_raw_data([1u32, 2]).try_build();
+    /// assert_eq!(missing, Err(BuilderError::NoContextTokenHeader));
+    /// ```
+    pub const fn try_build(self) -> Result<[u8; TOTAL], BuilderError>
+    where
+        Data: ~const IntoWords<LEN>,
+        InlineBuffer: ~const IntoBytes<INLINE_BUFFER_LEN>,
+    {
+        if let Err(err) = check_layout(SS, SB, RB, EB, RS, LEN, INLINE_BUFFER_LEN, SH_TOTAL, PB != 0) {
+            return Err(err);
+        }
+
+        match self.token_check() {
+            Ok(()) => Ok(self.build()),
+            Err(err) => Err(err)
         }
     }
 
     /// Builds the command into a sequence of bytes
+    ///
+    /// # Panicking
+    /// * The special header's size does not match the process ID and handles it carries
+    /// * The command holds more than [`MAX_DESCRIPTORS`] descriptors in total
+    /// * The command is a [`CommandType::RequestWithContext`] or
+    ///   [`CommandType::ControlWithContext`] whose raw data is not a CMIF request carrying a
+    ///   context token. This includes one made with [`new_builder`] whose raw data is not a CMIF
+    ///   request at all.
+    /// * The command is a [`CommandType::Request`] or [`CommandType::Control`] whose CMIF request
+    ///   header has [`cmif::CONTEXT_VERSION`](crate::cmif::CONTEXT_VERSION) in its version word,
+    ///   which marks it as carrying a token
+    /// * The command was given a token with [`HipcCommandBuilder::with_context_token`] but its
+    ///   raw data has no CMIF request header to hold it
+    ///
+    /// See [`check_context_token`] for the token checks.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, header};
+    ///
+    /// // The raw data lands directly after special headers of any size
+    /// let none = header::new_builder();
+    /// let pid = header::new_builder().with_program_id(0x10);
+    /// let handles = header::new_builder().with_copy_handle(1).with_copy_handle(2).with_move_handle(3);
+    /// let all = pid.merge(handles);
+    ///
+    /// macro_rules! check {
+    ///     ($header:expr) => {
+    ///         let bytes = command::request_builder()
+    ///             .with_special_header($header)
+    ///             .with_raw_data([0xAABB_CCDDu32])
+    ///             .build();
+    ///
+    ///         assert_eq!(bytes.len(), 8 + $header.byte_len() + 4);
+    ///         assert_eq!(&bytes[8..8 + $header.byte_len()], &$header.build());
+    ///         assert_eq!(&bytes[bytes.len() - 4..], &0xAABB_CCDDu32.to_le_bytes());
+    ///     };
+    /// }
+    ///
+    /// check!(none);
+    /// check!(pid);
+    /// check!(handles);
+    /// check!(all);
+    /// ```
+    ///
+    /// A context command must carry a context token, and only a context command may carry one:
+    /// ```
+    /// use hipc::{cmif::{self, CmifMode}, command, CommandType};
+    ///
+    /// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+    ///
+    /// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 1, 0)] =
+    ///     cmif::request_words(OFFSET, CmifMode::Session, 42, [7], &[]);
+    ///
+    /// let missing = std::panic::catch_unwind(|| {
+    ///     command::new_builder(CommandType::RequestWithContext).with_raw_data(words).build()
+    /// }).unwrap_err();
+    /// assert_eq!(*missing.downcast_ref::<&str>().unwrap(), "Context commands must carry a context token!");
+    ///
+    /// // A token written into the raw data by hand still makes the command a context command
+    /// let spurious = std::panic::catch_unwind(|| {
+    ///     command::request_builder().with_raw_data(cmif::with_context_token(words, OFFSET, 0x1234)).build()
+    /// }).unwrap_err();
+    /// assert_eq!(*spurious.downcast_ref::<&str>().unwrap(), "Only context commands can carry a context token!");
+    ///
+    /// // A context command whose raw data is not a CMIF request at all
+    /// let not_cmif = std::panic::catch_unwind(|| {
+    ///     command::new_builder(CommandType::ControlWithContext).with_raw_data([1u32, 2]).build()
+    /// }).unwrap_err();
+    /// assert_eq!(*not_cmif.downcast_ref::<&str>().unwrap(), "Context commands must carry a context token!");
+    ///
+    /// // A token with nowhere to be written
+    /// let no_header = std::panic::catch_unwind(|| {
+    ///     command::request_with_context_builder(0x1234).with_raw_data([1u32, 2]).build()
+    /// }).unwrap_err();
+    /// assert_eq!(
+    ///     *no_header.downcast_ref::<&str>().unwrap(),
+    ///     "The raw data has no CMIF request header to hold the context token!"
+    /// );
+    /// ```
+    ///
+    /// Both checks fail to compile when the command is built in a constant.
+    /// ```compile_fail,E0080
+    /// #![feature(const_trait_impl)]
+    /// use hipc::{command, CommandType};
+    ///
+    /// const MISSING: [u8; 8] = command::new_builder(CommandType::ControlWithContext).build();
+    /// ```
+    /// ```compile_fail,E0080
+    /// #![feature(const_trait_impl)]
+    /// use hipc::{cmif::{self, CmifMode}, command};
+    ///
+    /// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+    /// const WORDS: [u32; 6] = cmif::with_context_token(cmif::request_words(OFFSET, CmifMode::Session, 42, [], &[]), OFFSET, 1);
+    /// const SPURIOUS: [u8; 32] = command::control_builder().with_raw_data(WORDS).build();
+    /// ```
     pub const fn build(self) -> [u8; TOTAL]
     where
         Data: ~const IntoWords<LEN>,
         InlineBuffer: ~const IntoBytes<INLINE_BUFFER_LEN>,
     {
+        // The descriptors directly follow the special header, so it must be made of whole words
+        const_assert!(
+            SH_TOTAL % core::mem::size_of::<u32>() == 0,
+            "Special header is not a whole number of words!",
+            "special header bytes" => SH_TOTAL
+        );
+
+        // The section offsets are computed from SH_TOTAL, so it must match what the special header writes
+        const_assert!(
+            SH == 0 || SH_TOTAL == crate::header::consumed_space(SH_PIDS, SH_COPY, SH_MOVE),
+            "Special header size does not match its contents!",
+            "special header bytes" => SH_TOTAL,
+            "process IDs" => SH_PIDS,
+            "copy handles" => SH_COPY,
+            "move handles" => SH_MOVE
+        );
+
+        helpers::check_descriptor_count(SS, SB, RB, EB, RS, PB);
+        self.assert_token();
+
         let mut raw = [0u8; TOTAL];
 
+        let offsets = section_offsets(SS, SB, RB, EB, RS, LEN, INLINE_BUFFER_LEN, SH_TOTAL, PB != 0);
+
         let header = Header::new(
             self.ty as u16,
             SS,
@@ -725,101 +3128,691 @@ HipcCommandBuilder<SS, SB, RB, EB, RS, SH, PB, SH_PIDS, SH_COPY, SH_MOVE, SH_TOT
             EB,
             LEN,
             helpers::get_recv_mode(RS, INLINE_BUFFER_LEN, PB != 0),
-            0,
+            offsets.receive_list_offset(),
             SH != 0
         );
 
         let header_bytes: [u8; 8] = header.into();
 
-        raw = helpers::byte_array_write(raw, header_bytes, 0);
+        let mut section = 0;
+        while section < SECTION_ORDER.len() {
+            let kind = SECTION_ORDER[section];
+            let offset = offsets.of(kind);
+
+            let mut counter = 0;
+            match kind {
+                SectionKind::Header => {
+                    raw = helpers::section_write(raw, header_bytes, offset, "Header overruns the command!");
+                },
+                SectionKind::SpecialHeader => while counter < SH {
+                    let special_header_bytes = self.special_hdrs[counter].build();
+                    raw = helpers::section_write(raw, special_header_bytes, offset, "Special header overruns the command!");
+
+                    counter += 1;
+                },
+                SectionKind::SendStatics => while counter < SS {
+                    let desc_bytes: [u8; 8] = self.send_statics[counter].into();
+                    let write_index = offset + counter * desc_bytes.len();
+                    raw = helpers::section_write(raw, desc_bytes, write_index, "Send statics overrun the command!");
+
+                    counter += 1;
+                },
+                SectionKind::SendBuffers => while counter < SB {
+                    let desc_bytes: [u8; 12] = self.send_buffers[counter].into();
+                    let write_index = offset + counter * desc_bytes.len();
+                    raw = helpers::section_write(raw, desc_bytes, write_index, "Send buffers overrun the command!");
+
+                    counter += 1;
+                },
+                SectionKind::RecvBuffers => while counter < RB {
+                    let desc_bytes: [u8; 12] = self.recv_buffers[counter].into();
+                    let write_index = offset + counter * desc_bytes.len();
+                    raw = helpers::section_write(raw, desc_bytes, write_index, "Receive buffers overrun the command!");
+
+                    counter += 1;
+                },
+                SectionKind::ExchBuffers => while counter < EB {
+                    let desc_bytes: [u8; 12] = self.exch_buffers[counter].into();
+                    let write_index = offset + counter * desc_bytes.len();
+                    raw = helpers::section_write(raw, desc_bytes, write_index, "Exchange buffers overrun the command!");
+
+                    counter += 1;
+                },
+                SectionKind::RawData => {
+                    let data = self.raw_words(offset);
+                    while counter < LEN {
+                        let raw_bytes = data[counter].to_le_bytes();
+                        let write_index = offset + counter * raw_bytes.len();
+                        raw = helpers::section_write(raw, raw_bytes, write_index, "Raw data overruns the command!");
+
+                        counter += 1;
+                    }
+                },
+                SectionKind::InlineBuffer => if INLINE_BUFFER_LEN > 0 {
+                    let data: [u8; INLINE_BUFFER_LEN] = self.inline_buffer.into();
+                    raw = helpers::section_write(raw, data, offset, "Inline buffer overruns the command!");
+                },
+                SectionKind::PointerBuffer => while counter < PB {
+                    let raw_bytes: [u8; 8] = self.pointer_bufs[counter].into();
+                    raw = helpers::section_write(raw, raw_bytes, offset, "Pointer buffer overruns the command!");
+
+                    counter += 1;
+                },
+                SectionKind::RecvStatics => while counter < RS {
+                    let raw_bytes: [u8; 8] = self.recv_statics[counter].into();
+                    let write_index = offset + counter * raw_bytes.len();
+                    raw = helpers::section_write(raw, raw_bytes, write_index, "Receive statics overrun the command!");
+
+                    counter += 1;
+                }
+            }
+
+            section += 1;
+        }
 
-        let mut write_index = header_bytes.len();
+        raw
+    }
 
-        let mut counter = 0;
-        while counter < SH {
-            let special_header_bytes = self.special_hdrs[counter].build();
-            raw = helpers::byte_array_write(raw, special_header_bytes, write_index);
+    /// Walks every section of the serialized command in order, passing the bytes of each
+    /// piece to `sink`
+    ///
+    /// This produces the same layout as [`HipcCommandBuilder::build`] without materializing
+    /// the full command, and is shared by the runtime serialization paths.
+    ///
+    /// # Arguments
+    /// * `sink` - Receives each serialized piece, in order. Returning an error stops the walk.
+    ///
+    /// # Panicking
+    /// * The command's context token does not match its type, see [`check_context_token`]
+    #[track_caller]
+    fn emit_sections<E>(&self, sink: &mut impl FnMut(&[u8]) -> Result<(), E>) -> Result<(), E> {
+        self.assert_token();
 
-            write_index += special_header_bytes.len();
-            counter += 1;
-        }
+        let offsets = section_offsets(SS, SB, RB, EB, RS, LEN, INLINE_BUFFER_LEN, SH_TOTAL, PB != 0);
+        let header = Header::new(
+            self.ty as u16,
+            SS,
+            SB,
+            RB,
+            EB,
+            LEN,
+            helpers::get_recv_mode(RS, INLINE_BUFFER_LEN, PB != 0),
+            offsets.receive_list_offset(),
+            SH != 0
+        );
 
-        counter = 0;
-        while counter < SS {
-            let desc_bytes: [u8; 8] = self.send_statics[counter].into();
-            raw = helpers::byte_array_write(raw, desc_bytes, write_index);
+        let header_bytes: [u8; 8] = header.into();
 
-            write_index += desc_bytes.len();
-            counter += 1;
+        let mut written = 0;
+        for kind in SECTION_ORDER {
+            // Sections only ever need padding to reach a 16-byte alignment
+            let padding = offsets.of(kind) - written;
+            sink(&[0u8; 15][..padding])?;
+            written += padding;
+
+            let mut emit = |bytes: &[u8]| {
+                written += bytes.len();
+                sink(bytes)
+            };
+
+            match kind {
+                SectionKind::Header => emit(&header_bytes)?,
+                SectionKind::SpecialHeader => for special_header in self.special_hdrs {
+                    emit(&special_header.build())?;
+                },
+                SectionKind::SendStatics => for desc in self.send_statics {
+                    emit(&<[u8; 8]>::from(desc))?;
+                },
+                SectionKind::SendBuffers => for desc in self.send_buffers {
+                    emit(&<[u8; 12]>::from(desc))?;
+                },
+                SectionKind::RecvBuffers => for desc in self.recv_buffers {
+                    emit(&<[u8; 12]>::from(desc))?;
+                },
+                SectionKind::ExchBuffers => for desc in self.exch_buffers {
+                    emit(&<[u8; 12]>::from(desc))?;
+                },
+                SectionKind::RawData => {
+                    for word in self.raw_words(offsets.raw_data) {
+                        emit(&word.to_le_bytes())?;
+                    }
+                },
+                SectionKind::InlineBuffer => {
+                    let data: [u8; INLINE_BUFFER_LEN] = self.inline_buffer.into();
+                    emit(&data)?;
+                },
+                SectionKind::PointerBuffer => for entry in self.pointer_bufs {
+                    emit(&<[u8; 8]>::from(entry))?;
+                },
+                SectionKind::RecvStatics => for entry in self.recv_statics {
+                    emit(&<[u8; 8]>::from(entry))?;
+                }
+            }
         }
 
-        counter = 0;
-        while counter < SB {
-            let desc_bytes: [u8; 12] = self.send_buffers[counter].into();
-            raw = helpers::byte_array_write(raw, desc_bytes, write_index);
+        Ok(())
+    }
 
-            write_index += desc_bytes.len();
-            counter += 1;
+    /// Serializes the command directly into `dst`, such as the TLS
+    ///
+    /// This avoids the stack copy of [`HipcCommandBuilder::build`] for large commands. Only
+    /// `dst[..TOTAL]` is written, including the padding before the inline buffer.
+    ///
+    /// # Arguments
+    /// * `dst` - The buffer to serialize into
+    ///
+    /// # Returns
+    /// * The number of bytes written
+    ///
+    /// # Failures
+    /// * `dst` is shorter than the command
+    /// * The same situations as [`check_context_token`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, BuilderError}, packed::BufferDescriptor};
+    ///
+    /// let cmd = || command::request_builder()
+    ///     .with_send_buffer(BufferDescriptor::new(0x8000_0000, 0x100, 0))
+    ///     .with_raw_data([1u32, 2, 3]);
+    ///
+    /// let mut tls = [0xFFu8; 0x100];
+    /// let written = cmd().build_into(&mut tls).unwrap();
+    ///
+    /// assert_eq!(&tls[..written], &cmd().build());
+    /// assert!(tls[written..].iter().all(|byte| *byte == 0xFF));
+    ///
+    /// assert_eq!(
+    ///     cmd().build_into(&mut [0u8; 16]),
+    ///     Err(BuilderError::BufferTooSmall { needed: 32, available: 16 })
+    /// );
+    /// ```
+    pub fn build_into(self, dst: &mut [u8]) -> Result<usize, BuilderError> {
+        if dst.len() < TOTAL {
+            return Err(BuilderError::BufferTooSmall { needed: TOTAL, available: dst.len() });
         }
 
-        counter = 0;
-        while counter < RB {
-            let desc_bytes: [u8; 12] = self.recv_buffers[counter].into();
-            raw = helpers::byte_array_write(raw, desc_bytes, write_index);
+        self.token_check()?;
 
-            write_index += desc_bytes.len();
-            counter += 1;
+        Ok(self.build_at(dst, 0))
+    }
+
+    /// Serializes the command into possibly uninitialized memory, skipping the up-front zeroing
+    /// that [`HipcCommandBuilder::build`] performs
+    ///
+    /// Every byte of `dst[..TOTAL]` is written, including the padding before the inline buffer,
+    /// so that region is fully initialized once this returns. Bytes past `TOTAL` are untouched.
+    ///
+    /// # Arguments
+    /// * `dst` - The memory to serialize into, which must be at least `TOTAL` bytes long
+    ///
+    /// # Returns
+    /// * The initialized, serialized command at the start of `dst`
+    ///
+    /// # Panicking
+    /// * `dst` is shorter than the command
+    /// * The command's context token does not match its type, see [`check_context_token`]
+    ///
+    /// # Example
+    /// ```
+    /// use core::mem::MaybeUninit;
+    /// use hipc::{command, packed::BufferDescriptor};
+    ///
+    /// let cmd = || command::request_builder()
+    ///     .with_recv_buffer(BufferDescriptor::new(0x8000_0000, 0x100, 0))
+    ///     .with_raw_data([1u32, 2]);
+    ///
+    /// let mut tls = [MaybeUninit::<u8>::uninit(); 0x100];
+    /// let written = cmd().build_into_uninit(&mut tls);
+    ///
+    /// assert_eq!(written, cmd().build());
+    /// ```
+    pub fn build_into_uninit(self, dst: &mut [MaybeUninit<u8>]) -> &mut [u8] {
+        if dst.len() < TOTAL {
+            panic!("Destination is too small for the command!");
+        }
+
+        let mut written = 0;
+        let result = self.emit_sections(&mut |bytes: &[u8]| -> Result<(), Infallible> {
+            for (slot, byte) in dst[written..].iter_mut().zip(bytes) {
+                slot.write(*byte);
+            }
+            written += bytes.len();
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => {},
+            Err(never) => match never {}
         }
 
-        counter = 0;
-        while counter < EB {
-            let desc_bytes: [u8; 12] = self.exch_buffers[counter].into();
-            raw = helpers::byte_array_write(raw, desc_bytes, write_index);
+        debug_assert_eq!(written, TOTAL);
+
+        // SAFETY: `emit_sections` writes every byte of the command without gaps, so the first
+        //         `TOTAL` bytes of `dst` have all been initialized
+        unsafe { &mut *(&mut dst[..TOTAL] as *mut [MaybeUninit<u8>] as *mut [u8]) }
+    }
 
-            write_index += desc_bytes.len();
-            counter += 1;
+    /// Serializes the command into `out`, starting at `offset`
+    ///
+    /// This packs several commands into one buffer, as some transports batch messages. Only
+    /// `out[offset..end]` is written.
+    ///
+    /// # Arguments
+    /// * `out` - The buffer to serialize into
+    /// * `offset` - The byte offset in `out` to start the command at
+    ///
+    /// # Returns
+    /// * The offset just past the end of the command, where the next one can start
+    ///
+    /// # Panicking
+    /// * `out` is too short to hold the command at `offset`
+    /// * The command's context token does not match its type, see [`check_context_token`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, parse::ParsedCommand}, packed::BufferDescriptor};
+    ///
+    /// let mut batch = [0u8; 0x100];
+    /// let end = command::request_builder()
+    ///     .with_raw_data([1u32, 2])
+    ///     .build_at(&mut batch, 0);
+    /// let end = command::control_builder()
+    ///     .with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+    ///     .build_at(&mut batch, end);
+    /// assert_eq!(end, 16 + 20);
+    ///
+    /// let first = ParsedCommand::try_from(&batch[..]).unwrap();
+    /// assert_eq!(first.header().raw_data_len(), 2);
+    ///
+    /// let second = ParsedCommand::try_from(&batch[first.len()..]).unwrap();
+    /// assert_eq!(second.header().ty(), 5);
+    /// assert_eq!(second.header().num_send_buffers(), 1);
+    /// assert_eq!(first.len() + second.len(), end);
+    /// ```
+    pub fn build_at(self, out: &mut [u8], offset: usize) -> usize {
+        if out.len() < offset + TOTAL {
+            panic!("Destination is too small for the command!");
         }
 
-        let data: [u32; LEN] = self.raw_data.into();
-        counter = 0;
-        while counter < LEN {
-            let raw_bytes = data[counter].to_le_bytes();
-            raw = helpers::byte_array_write(raw, raw_bytes, write_index);
+        let mut written = offset;
+        let result = self.emit_sections(&mut |bytes: &[u8]| -> Result<(), Infallible> {
+            out[written..written + bytes.len()].copy_from_slice(bytes);
+            written += bytes.len();
+            Ok(())
+        });
 
-            write_index += raw_bytes.len();
-            counter += 1;
+        match result {
+            Ok(()) => {},
+            Err(never) => match never {}
         }
 
-        if INLINE_BUFFER_LEN > 0 {
-            let data: [u8; INLINE_BUFFER_LEN] = self.inline_buffer.into();
-            write_index = (write_index + 15) & !16;
-            helpers::byte_array_write(raw, data, write_index);
-            write_index += data.len();
+        written
+    }
+
+    /// Serializes the command into a buffer of words, such as a view of the TLS
+    ///
+    /// Each word holds four bytes of the command in little-endian order, the same as
+    /// [`HipcCommandBuilder::build`] reinterpreted as words. When the command does not end on a
+    /// word boundary the rest of the last word is zeroed.
+    ///
+    /// # Arguments
+    /// * `out` - The buffer to serialize into
+    ///
+    /// # Returns
+    /// * The number of words written
+    ///
+    /// # Panicking
+    /// * `out` is too short to hold the command
+    /// * The command's context token does not match its type, see [`check_context_token`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, packed::BufferDescriptor};
+    ///
+    /// let cmd = || command::request_builder()
+    ///     .with_send_buffer(BufferDescriptor::new(0x8000_0000, 0x100, 0))
+    ///     .with_raw_data([1u32, 2, 3]);
+    ///
+    /// let mut tls = [0xFFFF_FFFFu32; 0x40];
+    /// let written = cmd().build_words_into(&mut tls);
+    ///
+    /// let expected: Vec<u32> = cmd().build()
+    ///     .chunks(4)
+    ///     .map(|word| u32::from_le_bytes(word.try_into().unwrap()))
+    ///     .collect();
+    /// assert_eq!(&tls[..written], &expected[..]);
+    /// assert_eq!(tls[written], 0xFFFF_FFFF);
+    ///
+    /// // A 3 byte inline buffer leaves the last word partially filled
+    /// let mut tls = [0xFFFF_FFFFu32; 0x40];
+    /// let written = command::request_builder().with_inline_buffer([0xAAu8; 3]).build_words_into(&mut tls);
+    /// assert_eq!(&tls[..written], &[0x4, 0x400, 0, 0, 0x00AA_AAAA]);
+    /// ```
+    pub fn build_words_into(self, out: &mut [u32]) -> usize {
+        let words = helpers::align_up(TOTAL, core::mem::size_of::<u32>()) / core::mem::size_of::<u32>();
+        if out.len() < words {
+            panic!("Destination is too small for the command!");
         }
-        
-        counter = 0;
-        while counter < PB {
-            let raw_bytes: [u8; 8] = self.pointer_bufs[counter].into();
-            raw = helpers::byte_array_write(raw, raw_bytes, write_index);
 
-            write_index += raw_bytes.len();
-            counter += 1;
+        let mut written = 0;
+        let result = self.emit_sections(&mut |bytes: &[u8]| -> Result<(), Infallible> {
+            for byte in bytes {
+                let (word, shift) = (written / 4, (written % 4) * 8);
+                if shift == 0 {
+                    out[word] = 0;
+                }
+
+                out[word] |= (*byte as u32) << shift;
+                written += 1;
+            }
+            Ok(())
+        });
+
+        match result {
+            Ok(()) => {},
+            Err(never) => match never {}
         }
 
-        counter = 0;
-        while counter < RS {
-            let raw_bytes: [u8; 8] = self.recv_statics[counter].into();
-            raw = helpers::byte_array_write(raw, raw_bytes, write_index);
+        words
+    }
 
-            write_index += raw_bytes.len();
-            counter += 1;
+    /// Serializes the command and stores it alongside its shape in a [`BoxedCommand`]
+    ///
+    /// See [`BoxedCommand`] for an example.
+    ///
+    /// [`BoxedCommand`]: crate::boxed::BoxedCommand
+    #[cfg(feature = "alloc")]
+    pub fn into_boxed(self) -> crate::boxed::BoxedCommand {
+        crate::boxed::BoxedCommand {
+            ty: self.ty,
+            send_statics: SS,
+            send_buffers: SB,
+            recv_buffers: RB,
+            exch_buffers: EB,
+            recv_statics: RS,
+            special_header_bytes: SH_TOTAL,
+            has_pointer_buffer: PB != 0,
+            raw_data_words: LEN,
+            inline_buffer_len: INLINE_BUFFER_LEN,
+            bytes: alloc::boxed::Box::new(self.build())
         }
+    }
 
-        raw
+    /// Serializes the command into a fixed-capacity [`heapless::Vec`]
+    ///
+    /// This gives a growable buffer without needing a global allocator.
+    ///
+    /// # Failures
+    /// * The command is larger than `CAP`
+    /// * The same situations as [`check_context_token`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, BuilderError}, packed::BufferDescriptor};
+    ///
+    /// let cmd = || command::request_builder()
+    ///     .with_send_buffer(BufferDescriptor::new(0x8000_0000, 0x100, 0))
+    ///     .with_raw_data([1u32, 2, 3]);
+    ///
+    /// let out: heapless::Vec<u8, 256> = cmd().build_heapless().unwrap();
+    /// assert_eq!(&out[..], &cmd().build());
+    ///
+    /// assert_eq!(
+    ///     cmd().build_heapless::<16>().unwrap_err(),
+    ///     BuilderError::BufferTooSmall { needed: 32, available: 16 }
+    /// );
+    /// ```
+    #[cfg(feature = "heapless")]
+    pub fn build_heapless<const CAP: usize>(self) -> Result<heapless::Vec<u8, CAP>, BuilderError> {
+        if TOTAL > CAP {
+            return Err(BuilderError::BufferTooSmall { needed: TOTAL, available: CAP });
+        }
+
+        self.token_check()?;
+
+        let mut out = heapless::Vec::new();
+        self.emit_sections(&mut |bytes: &[u8]| out.extend_from_slice(bytes))
+            .map_err(|()| BuilderError::BufferTooSmall { needed: TOTAL, available: CAP })?;
+
+        Ok(out)
+    }
+
+    /// Serializes the command straight into a writer, section by section
+    ///
+    /// Unlike [`HipcCommandBuilder::build`], this does not materialize the full command
+    /// before writing it out.
+    ///
+    /// # Arguments
+    /// * `w` - The writer to serialize into
+    ///
+    /// # Returns
+    /// * The number of bytes written
+    ///
+    /// # Panicking
+    /// * The command's context token does not match its type, see [`check_context_token`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, packed::BufferDescriptor, CommandType};
+    ///
+    /// let cmd = command::new_builder(CommandType::Request)
+    ///     .with_send_buffer(BufferDescriptor::new(0x8000_0000, 0x100, 0))
+    ///     .with_raw_data([1u32, 2, 3]);
+    ///
+    /// let mut out = Vec::new();
+    /// let written = cmd.write_to(&mut out).unwrap();
+    ///
+    /// assert_eq!(written, out.len());
+    /// assert_eq!(out, cmd.build());
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn write_to<W: std::io::Write>(&self, w: &mut W) -> std::io::Result<usize> {
+        let mut written = 0;
+        self.emit_sections(&mut |bytes: &[u8]| -> std::io::Result<()> {
+            w.write_all(bytes)?;
+            written += bytes.len();
+            Ok(())
+        })?;
+
+        Ok(written)
     }
 }
 
+/// Fails to compile unless two builder expressions have the same shape
+///
+/// Two builders have the same shape when every section count, the special header layout,
+/// the raw data length, and the inline buffer length match. The expressions are type checked
+/// but never evaluated.
+///
+/// # Example
+/// ```
+/// use hipc::{command, packed::BufferDescriptor, raw_data};
+///
+/// // The order sections are added in doesn't matter
+/// command::assert_same_command_shape!(
+///     command::request_builder().with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0)).with_raw_data([1u32, 2]),
+///     command::request_builder().with_raw_data([3u32, 4]).with_send_buffer(BufferDescriptor::new(0x2000, 0x100, 0))
+/// );
+///
+/// // Nor does how the raw data is produced
+/// command::assert_same_command_shape!(
+///     command::request_builder().with_raw_data(raw_data::new_builder::<8>().with_args([1u32, 2])),
+///     command::request_builder().with_raw_data([0u32; 2])
+/// );
+/// ```
+///
+/// ```compile_fail
+/// use hipc::{command, packed::BufferDescriptor};
+///
+/// // A send buffer is not a receive buffer
+/// command::assert_same_command_shape!(
+///     command::request_builder().with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0)),
+///     command::request_builder().with_recv_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+/// );
+/// ```
+#[macro_export]
+macro_rules! assert_same_command_shape {
+    ($a:expr, $b:expr $(,)?) => {
+        // Bind each side before comparing, so that both types are fully resolved first
+        let _ = || {
+            let a = $a;
+            let b = $b;
+            $crate::command::helpers::same_shape(&a, &b);
+        };
+    };
+}
+
+pub use assert_same_command_shape;
+
+/// Builds a command, failing to compile unless it is exactly the expected number of bytes
+///
+/// The length is part of the built array's type, so the check costs nothing at runtime and a
+/// layout change shows up as a type mismatch at the call site. The builder itself may still
+/// use runtime values.
+///
+/// # Example
+/// ```
+/// use hipc::{command, packed::BufferDescriptor};
+///
+/// let address = 0x8000_0000;
+/// let bytes = command::sized_command!(
+///     28,
+///     command::request_builder()
+///         .with_send_buffer(BufferDescriptor::new(address, 0x100, 0))
+///         .with_raw_data([1u32, 2])
+/// );
+///
+/// assert_eq!(bytes.len(), 8 + 12 + 8);
+/// ```
+///
+/// ```compile_fail
+/// use hipc::command;
+///
+/// // Three words of raw data make a 20 byte command
+/// let bytes = command::sized_command!(16, command::request_builder().with_raw_data([1u32, 2, 3]));
+/// ```
+#[macro_export]
+macro_rules! sized_command {
+    ($len:expr, $builder:expr $(,)?) => {{
+        let bytes: [u8; $len] = $builder.build();
+        bytes
+    }};
+}
+
+pub use sized_command;
+
 /// Creates a new, empty builder for the command given the type
 pub const fn new_builder(ty: CommandType) -> make_ty!() {
-    HipcCommandBuilder::<0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, [u32; 0], [u8; 0]>::new(ty)
-}
\ No newline at end of file
+    HipcCommandBuilder::new(ty)
+}
+
+/// A serialized [`CommandType::Close`] command, which never carries anything beyond its header
+///
+/// This can be copied directly into the TLS to close a session without going through the builder.
+///
+/// # Example
+/// ```
+/// use hipc::{command, CommandType};
+///
+/// assert_eq!(command::CLOSE_COMMAND, command::new_builder(CommandType::Close).build());
+/// ```
+pub const CLOSE_COMMAND: [u8; 8] = new_builder(CommandType::Close).build();
+
+/// Serializes a [`CommandType::Close`] command, the same bytes as [`CLOSE_COMMAND`]
+///
+/// # Example
+/// ```
+/// use hipc::{command, packed::Header, CommandType};
+///
+/// let header = Header::from_bytes(command::close());
+///
+/// assert_eq!(header.ty(), CommandType::Close as u16);
+/// assert_eq!(header.num_send_statics(), 0);
+/// assert_eq!(header.num_send_buffers(), 0);
+/// assert_eq!(header.num_receive_buffers(), 0);
+/// assert_eq!(header.num_exchange_buffers(), 0);
+/// assert_eq!(header.raw_data_len(), 0);
+/// assert_eq!(header.receive_static_mode(), 0);
+/// assert_eq!(header.receive_list_offset(), 0);
+/// assert!(!header.has_special_header());
+/// ```
+pub const fn close() -> [u8; 8] {
+    CLOSE_COMMAND
+}
+
+/// Creates a new, empty builder for a [`CommandType::Request`] command
+///
+/// # Example
+/// ```
+/// use hipc::{command, CommandType};
+///
+/// let bytes = command::request_builder().build();
+/// assert_eq!(bytes, command::new_builder(CommandType::Request).build());
+/// ```
+pub const fn request_builder() -> make_ty!() {
+    new_builder(CommandType::Request)
+}
+
+/// Creates a new, empty builder for a [`CommandType::Control`] command
+///
+/// # Example
+/// ```
+/// use hipc::{command, CommandType};
+///
+/// let bytes = command::control_builder().build();
+/// assert_eq!(bytes, command::new_builder(CommandType::Control).build());
+/// ```
+pub const fn control_builder() -> make_ty!() {
+    new_builder(CommandType::Control)
+}
+
+/// Creates a new, empty builder for a [`CommandType::RequestWithContext`] command carrying `token`
+///
+/// This is the same as calling [`HipcCommandBuilder::with_context_token`] on a
+/// [`request_builder`]. The token is written into the CMIF request header when the command is
+/// built, so the raw data given to the builder must hold one.
+///
+/// # Example
+/// ```
+/// use hipc::{cmif::{self, CmifMode}, command::{self, parse::ParsedCommand}, CommandType};
+///
+/// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+///
+/// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 1, 0)] =
+///     cmif::request_words(OFFSET, CmifMode::Session, 42, [7], &[]);
+/// let bytes = command::request_with_context_builder(0x1234).with_raw_data(words).build();
+/// assert_eq!(bytes, command::request_builder().with_raw_data(words).with_context_token(0x1234).build());
+///
+/// let command = ParsedCommand::try_from(&bytes[..]).unwrap();
+/// assert_eq!(command.header().ty(), CommandType::RequestWithContext as u16);
+/// assert_eq!(command.context_token(), Some(0x1234));
+/// ```
+pub const fn request_with_context_builder(token: u32) -> make_ty!() {
+    request_builder().with_context_token(token)
+}
+
+/// Creates a new, empty builder for a [`CommandType::ControlWithContext`] command carrying `token`
+///
+/// This is the same as calling [`HipcCommandBuilder::with_context_token`] on a
+/// [`control_builder`]. The token is written into the CMIF request header when the command is
+/// built, so the raw data given to the builder must hold one.
+///
+/// # Example
+/// ```
+/// use hipc::{cmif::{self, CmifMode}, command::{self, parse::ParsedCommand}, CommandType};
+///
+/// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+///
+/// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 1, 0)] =
+///     cmif::request_words(OFFSET, CmifMode::Session, 42, [7], &[]);
+/// let bytes = command::control_with_context_builder(0x1234).with_raw_data(words).build();
+/// assert_eq!(bytes, command::control_builder().with_raw_data(words).with_context_token(0x1234).build());
+///
+/// let command = ParsedCommand::try_from(&bytes[..]).unwrap();
+/// assert_eq!(command.header().ty(), CommandType::ControlWithContext as u16);
+/// assert_eq!(command.context_token(), Some(0x1234));
+/// ```
+pub const fn control_with_context_builder(token: u32) -> make_ty!() {
+    control_builder().with_context_token(token)
+}
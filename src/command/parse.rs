@@ -0,0 +1,649 @@
+//! Decoding of received commands
+
+use crate::{
+    cmif,
+    header::SpecialHeaderView,
+    packed::{BufferDescriptor, Header, ReceiveListEntry, StaticDescriptor},
+    util::read_word,
+    CommandType,
+    ParseError
+};
+
+use super::{section_offsets, SectionKind, SectionOffsets, MAX_TLS_BUFFER_SIZE, SECTION_ORDER};
+
+/// Which section of a command a buffer descriptor was read from
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BufferKind {
+    /// A send buffer (InMapAlias)
+    Send,
+
+    /// A receive buffer (OutMapAlias)
+    Recv,
+
+    /// An exchange buffer (InOutMapAlias)
+    Exch
+}
+
+/// A received command, borrowed from the buffer it was read out of
+///
+/// Construct one with `ParsedCommand::try_from`, which validates the header before anything
+/// else is read.
+///
+/// # Example
+/// ```
+/// use hipc::{command::{self, parse::ParsedCommand}, header, packed::BufferDescriptor, ParseError};
+///
+/// fn command_len(bytes: &[u8]) -> Result<usize, ParseError> {
+///     let command = ParsedCommand::try_from(bytes)?;
+///     Ok(command.len())
+/// }
+///
+/// let bytes = command::request_builder()
+///     .with_special_header(header::new_builder().with_copy_handle(0xFFFF_8001))
+///     .with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+///     .with_raw_data([1u32, 2])
+///     .build();
+///
+/// let command = ParsedCommand::try_from(&bytes[..]).unwrap();
+/// assert_eq!(command.header().ty(), 4);
+/// assert_eq!(command.header().num_send_buffers(), 1);
+/// assert!(command.special_header().unwrap().copy_handles().eq([0xFFFF_8001]));
+/// assert_eq!(command.as_bytes(), &bytes);
+///
+/// // Anything after the command is not part of it
+/// let mut padded = [0u8; 0x100];
+/// padded[..bytes.len()].copy_from_slice(&bytes);
+/// assert_eq!(command_len(&padded), Ok(bytes.len()));
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct ParsedCommand<'a> {
+    header: Header,
+    special_header: Option<SpecialHeaderView<'a>>,
+    bytes: &'a [u8]
+}
+
+impl<'a> ParsedCommand<'a> {
+    /// Parses the command at the start of a buffer, the same as `ParsedCommand::try_from`
+    ///
+    /// # Failures
+    /// * The same situations as `ParsedCommand::try_from`
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{
+    ///     command::{self, parse::ParsedCommand},
+    ///     header,
+    ///     packed::{BufferDescriptor, ReceiveListEntry, StaticDescriptor}
+    /// };
+    ///
+    /// let statics = [StaticDescriptor::new(0, 0x10, 0x1000), StaticDescriptor::new(1, 0x20, 0x2000)];
+    /// let send = BufferDescriptor::new(0x3000, 0x100, 0);
+    /// let recv = BufferDescriptor::new(0x4000, 0x200, 1);
+    /// let exch = BufferDescriptor::new(0x5000, 0x300, 3);
+    /// let entries = [ReceiveListEntry::new(0x6000, 0x40), ReceiveListEntry::new(0x7000, 0x80)];
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_special_header(header::new_builder().with_copy_handle(0xFFFF_8001))
+    ///     .with_send_static(statics[0])
+    ///     .with_send_static(statics[1])
+    ///     .with_send_buffer(send)
+    ///     .with_recv_buffer(recv)
+    ///     .with_exch_buffer(exch)
+    ///     .with_raw_data([0xAAAA_AAAAu32, 0xBBBB_BBBB])
+    ///     .with_recv_statics(entries)
+    ///     .build();
+    ///
+    /// let command = ParsedCommand::new(&bytes).unwrap();
+    /// assert!(command.send_statics_iter().eq(statics));
+    /// assert!(command.send_buffers_iter().eq([send]));
+    /// assert!(command.recv_buffers_iter().eq([recv]));
+    /// assert!(command.exch_buffers_iter().eq([exch]));
+    /// assert!(command.recv_list_iter().eq(entries));
+    /// assert_eq!(command.raw_data(), &[0xAA, 0xAA, 0xAA, 0xAA, 0xBB, 0xBB, 0xBB, 0xBB]);
+    ///
+    /// // Declared sections running past the end of the buffer
+    /// assert!(ParsedCommand::new(&bytes[..bytes.len() - 1]).is_err());
+    /// ```
+    pub fn new(bytes: &'a [u8]) -> Result<Self, ParseError> {
+        Self::try_from(bytes)
+    }
+
+    /// The command header
+    pub const fn header(&self) -> Header {
+        self.header
+    }
+
+    /// The two header words exactly as they were received
+    ///
+    /// Useful for forwarding or hashing a command without decoding the header and encoding it
+    /// again.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::command::{self, parse::ParsedCommand};
+    ///
+    /// let bytes = command::request_builder().with_raw_data([1u32, 2, 3]).build();
+    /// let command = ParsedCommand::try_from(&bytes[..]).unwrap();
+    ///
+    /// assert_eq!(command.header_words(), [
+    ///     u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    ///     u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]])
+    /// ]);
+    /// assert_eq!(command.header_words(), [0x4, 0x3]);
+    /// ```
+    pub const fn header_words(&self) -> [u32; 2] {
+        [read_word(self.bytes, 0), read_word(self.bytes, 4)]
+    }
+
+    /// The special header, if the command has one
+    pub const fn special_header(&self) -> Option<SpecialHeaderView<'a>> {
+        self.special_header
+    }
+
+    /// The bytes of the command, excluding anything that followed it in the source buffer
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    /// The size of the command, in bytes
+    pub const fn len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// Always `false`, a command contains at least its header
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// The context token of a context command
+    ///
+    /// # Returns
+    /// * `None` if the command is not a context command, or its raw data does not hold a CMIF
+    ///   request header carrying a token
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{cmif::{self, CmifMode}, command::{self, parse::ParsedCommand}};
+    ///
+    /// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+    ///
+    /// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 0, 0)] =
+    ///     cmif::request_words(OFFSET, CmifMode::Session, 42, [], &[]);
+    ///
+    /// let bytes = command::request_builder().with_raw_data(words).build();
+    /// assert_eq!(ParsedCommand::try_from(&bytes[..]).unwrap().context_token(), None);
+    ///
+    /// let bytes = command::control_builder().with_raw_data(words).with_context_token(5).build();
+    /// let command = ParsedCommand::try_from(&bytes[..]).unwrap();
+    /// assert_eq!(command.header().ty(), 7);
+    /// assert_eq!(command.context_token(), Some(5));
+    /// ```
+    pub const fn context_token(&self) -> Option<u32> {
+        let ty = self.header.ty();
+        if ty != CommandType::RequestWithContext as u16 && ty != CommandType::ControlWithContext as u16 {
+            return None;
+        }
+
+        let offsets = self.offsets();
+        let end = offsets.raw_data + self.header.raw_data_len() * core::mem::size_of::<u32>();
+        if end > self.bytes.len() {
+            return None;
+        }
+
+        let (raw, _) = self.bytes.split_at(end);
+        let (_, raw) = raw.split_at(offsets.raw_data);
+
+        match cmif::request_context_token(raw, offsets.raw_data) {
+            Ok(token) => token,
+            Err(_) => None
+        }
+    }
+
+    /// The send statics (InPointers) of the command, in the order they were written
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, parse::ParsedCommand}, packed::StaticDescriptor};
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_send_static(StaticDescriptor::new(0, 0x10, 0x1000))
+    ///     .with_send_static(StaticDescriptor::new(1, 0x20, 0x12_3456_7000))
+    ///     .with_raw_data([1u32])
+    ///     .build();
+    ///
+    /// let command = ParsedCommand::try_from(&bytes[..]).unwrap();
+    /// let indices: Vec<usize> = command.send_statics_iter().map(StaticDescriptor::index).collect();
+    /// assert_eq!(indices, [0, 1]);
+    /// ```
+    pub fn send_statics_iter(&self) -> impl Iterator<Item = StaticDescriptor> + 'a {
+        self.section(SectionKind::SendStatics)
+            .chunks_exact(core::mem::size_of::<StaticDescriptor>())
+            .map(|chunk| StaticDescriptor::from_bytes(to_array(chunk)))
+    }
+
+    /// The memory each send static points at, as `(address, size)` pairs
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, parse::ParsedCommand}, packed::StaticDescriptor};
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_send_static(StaticDescriptor::new(0, 0x10, 0x1000))
+    ///     .with_send_static(StaticDescriptor::new(1, 0x20, 0x12_3456_7000))
+    ///     .build();
+    ///
+    /// let command = ParsedCommand::try_from(&bytes[..]).unwrap();
+    /// let regions: Vec<(u64, usize)> = command.send_static_regions().collect();
+    /// assert_eq!(regions, [(0x1000, 0x10), (0x12_3456_7000, 0x20)]);
+    /// ```
+    pub fn send_static_regions(&self) -> impl Iterator<Item = (u64, usize)> + 'a {
+        self.send_statics_iter().map(|desc| (desc.address(), desc.size()))
+    }
+
+    /// The send buffers (InMapAliases) of the command, in the order they were written
+    pub fn send_buffers_iter(&self) -> impl Iterator<Item = BufferDescriptor> + 'a {
+        Self::buffers_in(self.section(SectionKind::SendBuffers))
+    }
+
+    /// The receive buffers (OutMapAliases) of the command, in the order they were written
+    pub fn recv_buffers_iter(&self) -> impl Iterator<Item = BufferDescriptor> + 'a {
+        Self::buffers_in(self.section(SectionKind::RecvBuffers))
+    }
+
+    /// The exchange buffers (InOutMapAliases) of the command, in the order they were written
+    pub fn exch_buffers_iter(&self) -> impl Iterator<Item = BufferDescriptor> + 'a {
+        Self::buffers_in(self.section(SectionKind::ExchBuffers))
+    }
+
+    /// Every buffer descriptor of the command, tagged with the section it came from
+    ///
+    /// Send buffers come first, then receive buffers, then exchange buffers, matching their
+    /// order in the command.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, parse::{BufferKind, ParsedCommand}}, packed::BufferDescriptor};
+    ///
+    /// let buffer = |index: u64| BufferDescriptor::new(0x1000 * index, 0x100, 0);
+    ///
+    /// // Sections are added out of order, they are still serialized in command order
+    /// let bytes = command::request_builder()
+    ///     .with_exch_buffer(buffer(4))
+    ///     .with_send_buffer(buffer(1))
+    ///     .with_recv_buffer(buffer(3))
+    ///     .with_exch_buffer(buffer(5))
+    ///     .with_send_buffer(buffer(2))
+    ///     .with_exch_buffer(buffer(6))
+    ///     .build();
+    ///
+    /// let command = ParsedCommand::new(&bytes).unwrap();
+    /// let buffers: Vec<(BufferKind, BufferDescriptor)> = command.buffers().collect();
+    /// assert_eq!(buffers, [
+    ///     (BufferKind::Send, buffer(1)),
+    ///     (BufferKind::Send, buffer(2)),
+    ///     (BufferKind::Recv, buffer(3)),
+    ///     (BufferKind::Exch, buffer(4)),
+    ///     (BufferKind::Exch, buffer(5)),
+    ///     (BufferKind::Exch, buffer(6))
+    /// ]);
+    /// ```
+    pub fn buffers(&self) -> impl Iterator<Item = (BufferKind, BufferDescriptor)> + 'a {
+        let send = self.send_buffers_iter().map(|desc| (BufferKind::Send, desc));
+        let recv = self.recv_buffers_iter().map(|desc| (BufferKind::Recv, desc));
+        let exch = self.exch_buffers_iter().map(|desc| (BufferKind::Exch, desc));
+
+        send.chain(recv).chain(exch)
+    }
+
+    /// The entries of the receive list, either a single pointer buffer or the receive statics
+    pub fn recv_list_iter(&self) -> impl Iterator<Item = ReceiveListEntry> + 'a {
+        let pointer_buffer = self.section(SectionKind::PointerBuffer);
+        let recv_statics = self.section(SectionKind::RecvStatics);
+
+        pointer_buffer.chunks_exact(core::mem::size_of::<ReceiveListEntry>())
+            .chain(recv_statics.chunks_exact(core::mem::size_of::<ReceiveListEntry>()))
+            .map(|chunk| ReceiveListEntry::from_bytes(to_array(chunk)))
+    }
+
+    /// The raw data payload
+    pub fn raw_data(&self) -> &'a [u8] {
+        self.section(SectionKind::RawData)
+    }
+
+    /// The raw data payload as little-endian words
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{cmif::{self, CmifMode}, command::{self, parse::ParsedCommand}, packed::BufferDescriptor};
+    ///
+    /// let bytes = command::request_builder().with_raw_data([1u32, 0xDEAD_BEEF, 3]).build();
+    /// let command = ParsedCommand::new(&bytes).unwrap();
+    /// assert!(command.raw_data_words().eq([1, 0xDEAD_BEEF, 3]));
+    ///
+    /// // A CMIF request, padded out to the 16-byte aligned CMIF region after the send buffer
+    /// const OFFSET: usize = cmif::layout(0, 1, 0, 0, 0, false).raw_data;
+    /// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 2, 0)] =
+    ///     cmif::request_words(OFFSET, CmifMode::Session, 42, [0xAAAA_AAAA, 0xBBBB_BBBB], &[]);
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+    ///     .with_raw_data(words)
+    ///     .build();
+    /// let command = ParsedCommand::new(&bytes).unwrap();
+    ///
+    /// assert_eq!(words[..3], [0, 0, 0]);
+    /// assert!(command.raw_data_words().eq(words));
+    /// ```
+    pub fn raw_data_words(&self) -> impl Iterator<Item = u32> + 'a {
+        self.raw_data()
+            .chunks_exact(core::mem::size_of::<u32>())
+            .map(|word| u32::from_le_bytes(to_array(word)))
+    }
+
+    /// The bytes of one section of the command
+    ///
+    /// The inline buffer is not declared in the header, so that section only holds whatever
+    /// lies between the raw data and a receive list the header's receive list offset places
+    /// further on. The receive list starts at that offset when one is set.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, parse::ParsedCommand, SectionKind}, packed::BufferDescriptor};
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+    ///     .with_raw_data([1u32])
+    ///     .build();
+    ///
+    /// let command = ParsedCommand::new(&bytes).unwrap();
+    /// assert_eq!(command.section(SectionKind::Header), &bytes[..8]);
+    /// assert_eq!(command.section(SectionKind::SendBuffers), &bytes[8..20]);
+    /// assert_eq!(command.section(SectionKind::RawData), &bytes[20..]);
+    /// assert!(command.section(SectionKind::SendStatics).is_empty());
+    /// ```
+    pub fn section(&self, kind: SectionKind) -> &'a [u8] {
+        let (start, end) = self.section_range(kind);
+        self.bytes.get(start..end).unwrap_or(&[])
+    }
+
+    /// Every section of the command in [`SECTION_ORDER`], with its bytes
+    ///
+    /// The sections tile the command, so joining their bytes gives back
+    /// [`as_bytes`](ParsedCommand::as_bytes). See [`ParsedCommand::section`] for where each one
+    /// is found.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{
+    ///     command::{self, parse::ParsedCommand, SectionKind},
+    ///     header,
+    ///     packed::{Header, ReceiveListEntry}
+    /// };
+    ///
+    /// let bytes = command::request_builder()
+    ///     .with_special_header(header::new_builder().with_copy_handle(0xFFFF_8001))
+    ///     .with_raw_data([1u32, 2, 3])
+    ///     .with_recv_static(ReceiveListEntry::new(0x2000, 0x100))
+    ///     .build();
+    ///
+    /// // Move the receive list 16 bytes further on and fill the gap with an inline payload
+    /// let list_start = bytes.len() - 8;
+    /// let header = Header::from_bytes(bytes[..8].try_into().unwrap());
+    /// let header: [u8; 8] = header.with_receive_list_offset(list_start / 4 + 4).into();
+    ///
+    /// let mut moved = Vec::new();
+    /// moved.extend_from_slice(&header);
+    /// moved.extend_from_slice(&bytes[8..list_start]);
+    /// moved.extend_from_slice(&[0xAA; 0x10]);
+    /// moved.extend_from_slice(&bytes[list_start..]);
+    ///
+    /// let command = ParsedCommand::new(&moved).unwrap();
+    /// let sections: Vec<(SectionKind, &[u8])> = command.sections().collect();
+    /// assert_eq!(sections.len(), command::SECTION_ORDER.len());
+    ///
+    /// let joined: Vec<u8> = sections.iter().flat_map(|(_, bytes)| bytes.iter().copied()).collect();
+    /// assert_eq!(joined, moved);
+    ///
+    /// assert_eq!(command.section(SectionKind::InlineBuffer), &[0xAA; 0x10]);
+    /// assert!(command.recv_list_iter().eq([ReceiveListEntry::new(0x2000, 0x100)]));
+    /// ```
+    pub fn sections(&self) -> impl Iterator<Item = (SectionKind, &'a [u8])> + 'a {
+        let command = *self;
+        SECTION_ORDER.into_iter().map(move |kind| (kind, command.section(kind)))
+    }
+
+    /// The byte range of one section of the command
+    fn section_range(&self, kind: SectionKind) -> (usize, usize) {
+        let offsets = self.offsets();
+
+        let mut index = 0;
+        while SECTION_ORDER[index] != kind {
+            index += 1;
+        }
+
+        let start = offsets.of(kind);
+        let end = match SECTION_ORDER.get(index + 1) {
+            Some(next) => offsets.of(*next),
+            None => offsets.end
+        };
+
+        if self.header.receive_list_offset() == 0 {
+            return (start, end);
+        }
+
+        // Receive list entries move as a block to the offset the header gives them, anything
+        // skipped over to get there belongs to the inline buffer
+        let list_start = self.header.receive_list_offset() * core::mem::size_of::<u32>();
+        match kind {
+            SectionKind::InlineBuffer => (start, list_start.max(start)),
+            SectionKind::PointerBuffer | SectionKind::RecvStatics => {
+                (list_start + (start - offsets.pointer_buffer), list_start + (end - offsets.pointer_buffer))
+            },
+            _ => (start, end)
+        }
+    }
+
+    /// Decodes every buffer descriptor in a section
+    fn buffers_in(section: &'a [u8]) -> impl Iterator<Item = BufferDescriptor> + 'a {
+        section.chunks_exact(core::mem::size_of::<BufferDescriptor>())
+            .map(|chunk| BufferDescriptor::from_bytes(to_array(chunk)))
+    }
+
+    /// The offsets of each section, as declared by the header
+    const fn offsets(&self) -> SectionOffsets {
+        let mode = self.header.receive_static_mode() as usize;
+        let special_header_total = match self.special_header {
+            Some(view) => view.byte_len(),
+            None => 0
+        };
+
+        section_offsets(
+            self.header.num_send_statics(),
+            self.header.num_send_buffers(),
+            self.header.num_receive_buffers(),
+            self.header.num_exchange_buffers(),
+            mode.saturating_sub(2),
+            self.header.raw_data_len(),
+            0,
+            special_header_total,
+            mode == 2
+        )
+    }
+}
+
+/// Parses the command at the start of a buffer
+///
+/// # Failures
+/// * The buffer is too short for the header, the special header, or any of the declared sections
+/// * The header's reserved bits are not zero
+/// * The header's receive list offset points before the end of the raw data
+/// * The declared sections do not fit within the TLS
+///
+/// # Example
+/// ```
+/// use hipc::{command::{self, parse::ParsedCommand}, packed::Header, ParseError};
+///
+/// let bytes = command::request_builder().with_raw_data([1u32, 2, 3]).build();
+/// assert!(ParsedCommand::try_from(&bytes[..]).is_ok());
+///
+/// // Not even a full header
+/// assert_eq!(
+///     ParsedCommand::try_from(&bytes[..6]).unwrap_err(),
+///     ParseError::Truncated { needed: 8, available: 6 }
+/// );
+///
+/// // Missing the last raw data word
+/// assert_eq!(
+///     ParsedCommand::try_from(&bytes[..16]).unwrap_err(),
+///     ParseError::Truncated { needed: 20, available: 16 }
+/// );
+///
+/// // A special header bit with no room for the special header
+/// let header: [u8; 8] = Header::new(4, 0, 0, 0, 0, 0, 0, 0, true).into();
+/// assert_eq!(
+///     ParsedCommand::try_from(&header[..]).unwrap_err(),
+///     ParseError::TruncatedSpecialHeader { needed: 4, available: 0 }
+/// );
+///
+/// // Reserved bits set
+/// let header: [u8; 8] = Header::new_raw([0x4, 0x1 << 14]).into();
+/// assert_eq!(ParsedCommand::try_from(&header[..]).unwrap_err(), ParseError::ReservedBitsSet(0x1));
+///
+/// // A receive list offset pointing into the 8 words of raw data
+/// let header: [u8; 8] = Header::new(4, 0, 0, 0, 0, 8, 2, 2, false).into();
+/// let mut tls = [0u8; 0x100];
+/// tls[..8].copy_from_slice(&header);
+/// assert_eq!(
+///     ParsedCommand::try_from(&tls[..]).unwrap_err(),
+///     ParseError::MisplacedReceiveList { offset: 8, raw_data_end: 8 + 8 * 4 }
+/// );
+///
+/// // 0x3FF words of raw data can never fit in the TLS
+/// let header: [u8; 8] = Header::new(4, 0, 0, 0, 0, 0x3FF, 0, 0, false).into();
+/// let mut tls = [0u8; 0x100];
+/// tls[..8].copy_from_slice(&header);
+/// assert_eq!(ParsedCommand::try_from(&tls[..]).unwrap_err(), ParseError::TooLarge(8 + 0x3FF * 4));
+/// ```
+impl<'a> TryFrom<&'a [u8]> for ParsedCommand<'a> {
+    type Error = ParseError;
+
+    fn try_from(bytes: &'a [u8]) -> Result<Self, Self::Error> {
+        let header_len = core::mem::size_of::<Header>();
+        if bytes.len() < header_len {
+            return Err(ParseError::Truncated { needed: header_len, available: bytes.len() });
+        }
+
+        let header = Header::new_raw([read_word(bytes, 0), read_word(bytes, 4)]);
+        if header.reserved() != 0 {
+            return Err(ParseError::ReservedBitsSet(header.reserved()));
+        }
+
+        let special_header = if header.has_special_header() {
+            Some(SpecialHeaderView::from_bytes(&bytes[header_len..])?)
+        } else {
+            None
+        };
+
+        let command = Self { header, special_header, bytes };
+
+        let list_start = header.receive_list_offset() * core::mem::size_of::<u32>();
+        let raw_data_end = command.offsets().inline_buffer;
+        if list_start != 0 && list_start < raw_data_end {
+            return Err(ParseError::MisplacedReceiveList { offset: list_start, raw_data_end });
+        }
+
+        let len = header.total_len(special_header.map_or(0, |view| view.byte_len()));
+        if len > MAX_TLS_BUFFER_SIZE {
+            return Err(ParseError::TooLarge(len));
+        }
+
+        if bytes.len() < len {
+            return Err(ParseError::Truncated { needed: len, available: bytes.len() });
+        }
+
+        Ok(Self {
+            header,
+            special_header,
+            bytes: &bytes[..len]
+        })
+    }
+}
+
+/// Parses the command in an IPC buffer given its address, such as a mock of the TLS
+///
+/// This is what [`read_from_tls`] uses once it has found the TLS, and lets the same path run
+/// against any buffer laid out like it.
+///
+/// # Safety
+/// * `tls` must be valid for reads of the full IPC buffer (0x100 bytes) for `'a`
+/// * Nothing may write to the buffer while the returned command is alive, including another
+///   IPC call from the same thread
+///
+/// # Failures
+/// * The same situations as `ParsedCommand::try_from`
+///
+/// # Example
+/// ```
+/// use hipc::{command::{self, parse}, packed::BufferDescriptor};
+///
+/// #[repr(align(16))]
+/// struct MockTls([u8; 0x100]);
+///
+/// let bytes = command::request_builder()
+///     .with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+///     .with_raw_data([1u32, 2])
+///     .build();
+///
+/// let mut tls = MockTls([0xFF; 0x100]);
+/// tls.0[..bytes.len()].copy_from_slice(&bytes);
+///
+/// // SAFETY: the mock TLS is a full IPC buffer and is not written to while it is borrowed
+/// let command = unsafe { parse::read_from_tls_ptr(tls.0.as_ptr()) }.unwrap();
+/// assert_eq!(command.as_bytes(), &bytes);
+/// assert!(command.raw_data_words().eq([1, 2]));
+/// ```
+#[cfg(feature = "on-device")]
+pub unsafe fn read_from_tls_ptr<'a>(tls: *const u8) -> Result<ParsedCommand<'a>, ParseError> {
+    // SAFETY: the caller guarantees `tls` is readable for the whole IPC buffer and stays
+    //         unchanged for `'a`
+    let bytes = unsafe { core::slice::from_raw_parts(tls, MAX_TLS_BUFFER_SIZE) };
+    ParsedCommand::try_from(bytes)
+}
+
+/// Parses the command the kernel left in the current thread's TLS IPC buffer
+///
+/// On the server side this is the incoming request, and on the client side the response to
+/// the last request sent from this thread.
+///
+/// # Safety
+/// * Must be called on the thread that received the command, the TLS is per-thread
+/// * No IPC call may be made from this thread while the returned command is alive, as the
+///   kernel writes the next message into the same buffer
+/// * Nothing else may write to the start of the TLS while the returned command is alive
+///
+/// # Failures
+/// * The same situations as `ParsedCommand::try_from`
+#[cfg(all(feature = "on-device", target_os = "horizon"))]
+pub unsafe fn read_from_tls<'a>() -> Result<ParsedCommand<'a>, ParseError> {
+    let tls: *const u8;
+
+    // SAFETY: `tpidrro_el0` holds the address of the current thread's TLS, which is always
+    //         mapped and starts with the IPC buffer
+    unsafe {
+        core::arch::asm!(
+            "mrs {}, tpidrro_el0",
+            out(reg) tls,
+            options(nomem, nostack, preserves_flags)
+        );
+    }
+
+    // SAFETY: the IPC buffer is the first 0x100 bytes of the TLS, the caller upholds the rest
+    unsafe { read_from_tls_ptr(tls) }
+}
+
+/// Copies a chunk of exactly `N` bytes into an array
+fn to_array<const N: usize>(chunk: &[u8]) -> [u8; N] {
+    let mut bytes = [0u8; N];
+    bytes.copy_from_slice(chunk);
+    bytes
+}
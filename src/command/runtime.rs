@@ -0,0 +1,580 @@
+//! Building commands whose shape is only known at runtime
+
+use crate::{
+    header::{consumed_space, Handle, HandleKind, MAX_COPY, MAX_MOVE},
+    packed::{BufferDescriptor, Header, ReceiveListEntry, SpecialHeader, StaticDescriptor},
+    CommandType
+};
+
+use super::{
+    check_context_token,
+    check_layout,
+    check_section,
+    helpers,
+    section_offsets,
+    BuilderError,
+    Section,
+    MAX_EXCH_BUFFERS,
+    MAX_RAW_DATA_WORDS,
+    MAX_RECV_BUFFERS,
+    MAX_RECV_STATICS,
+    MAX_SEND_BUFFERS,
+    MAX_SEND_STATICS,
+    MAX_TLS_BUFFER_SIZE
+};
+
+/// The most raw data words that can fit in the TLS after the header
+const MAX_RAW_WORDS: usize = (MAX_TLS_BUFFER_SIZE - core::mem::size_of::<Header>()) / core::mem::size_of::<u32>();
+
+/// A command builder which checks its limits at runtime instead of at compile time
+///
+/// [`HipcCommandBuilder`](super::HipcCommandBuilder) encodes the shape of the command in its
+/// type, so exceeding a limit fails to compile. That is not an option when the number of
+/// buffers or handles comes from runtime data, so every method here returns a
+/// [`BuilderError`] instead.
+///
+/// # Example
+/// ```
+/// use hipc::{
+///     command::{self, runtime::TryHipcCommandBuilder, BuilderError, Section},
+///     packed::{BufferDescriptor, ReceiveListEntry},
+///     CommandType
+/// };
+///
+/// fn with_handles(handles: &[u32]) -> Result<TryHipcCommandBuilder, BuilderError> {
+///     let mut builder = TryHipcCommandBuilder::new(CommandType::Request);
+///     for handle in handles {
+///         builder = builder.try_with_copy_handle(*handle)?;
+///     }
+///
+///     builder.try_with_raw_data(&[1, 2])
+/// }
+///
+/// // The same bytes as the compile time builder
+/// let mut out = [0u8; 0x100];
+/// let len = with_handles(&[0xFFFF_8001]).unwrap().build_into(&mut out).unwrap();
+/// let expected = command::request_builder()
+///     .with_special_header(hipc::header::new_builder().with_copy_handle(0xFFFF_8001))
+///     .with_raw_data([1u32, 2])
+///     .build();
+/// assert_eq!(&out[..len], &expected);
+///
+/// assert_eq!(with_handles(&[0; 16]).err(), Some(BuilderError::TooManyHandles { max: 15 }));
+///
+/// let mut builder = TryHipcCommandBuilder::new(CommandType::Request);
+/// assert_eq!(
+///     builder.try_with_raw_data(&[0; 0x40]).err(),
+///     Some(BuilderError::TlsOverflow { needed: 8 + 0x100, max: 0x100 })
+/// );
+/// assert_eq!(
+///     builder.try_with_inline_buffer(&[0; 4]).unwrap().try_with_pointer_buffer(ReceiveListEntry::new(0x1000, 0x10)).err(),
+///     Some(BuilderError::IllegalRecvList)
+/// );
+///
+/// for _ in 0..15 {
+///     builder = builder.try_with_send_buffer(BufferDescriptor::normal(0x1000, 0x100)).unwrap();
+/// }
+/// assert_eq!(
+///     builder.try_with_send_buffer(BufferDescriptor::normal(0x1000, 0x100)).err(),
+///     Some(BuilderError::SectionFull { section: Section::SendBuffers, max: 15 })
+/// );
+/// ```
+#[derive(Copy, Clone)]
+pub struct TryHipcCommandBuilder {
+    ty: CommandType,
+    send_statics: [StaticDescriptor; MAX_SEND_STATICS],
+    num_send_statics: usize,
+    send_buffers: [BufferDescriptor; MAX_SEND_BUFFERS],
+    num_send_buffers: usize,
+    recv_buffers: [BufferDescriptor; MAX_RECV_BUFFERS],
+    num_recv_buffers: usize,
+    exch_buffers: [BufferDescriptor; MAX_EXCH_BUFFERS],
+    num_exch_buffers: usize,
+    recv_statics: [ReceiveListEntry; MAX_RECV_STATICS],
+    num_recv_statics: usize,
+    pointer_buffer: Option<ReceiveListEntry>,
+    process_id: Option<u64>,
+    copy_handles: [u32; MAX_COPY],
+    num_copy_handles: usize,
+    move_handles: [u32; MAX_MOVE],
+    num_move_handles: usize,
+    raw_data: [u32; MAX_RAW_WORDS],
+    raw_data_len: usize,
+    inline_buffer: [u8; MAX_TLS_BUFFER_SIZE],
+    inline_buffer_len: usize
+}
+
+impl TryHipcCommandBuilder {
+    /// Creates a new, empty builder for the command given the type
+    pub fn new(ty: CommandType) -> Self {
+        Self {
+            ty,
+            send_statics: [StaticDescriptor::default(); MAX_SEND_STATICS],
+            num_send_statics: 0,
+            send_buffers: [BufferDescriptor::default(); MAX_SEND_BUFFERS],
+            num_send_buffers: 0,
+            recv_buffers: [BufferDescriptor::default(); MAX_RECV_BUFFERS],
+            num_recv_buffers: 0,
+            exch_buffers: [BufferDescriptor::default(); MAX_EXCH_BUFFERS],
+            num_exch_buffers: 0,
+            recv_statics: [ReceiveListEntry::default(); MAX_RECV_STATICS],
+            num_recv_statics: 0,
+            pointer_buffer: None,
+            process_id: None,
+            copy_handles: [0; MAX_COPY],
+            num_copy_handles: 0,
+            move_handles: [0; MAX_MOVE],
+            num_move_handles: 0,
+            raw_data: [0; MAX_RAW_WORDS],
+            raw_data_len: 0,
+            inline_buffer: [0; MAX_TLS_BUFFER_SIZE],
+            inline_buffer_len: 0
+        }
+    }
+
+    /// Adds a static/InPointer to this command (max 15)
+    ///
+    /// # Failures
+    /// * The send statics are full
+    /// * The command no longer fits in the TLS
+    pub fn try_with_send_static(mut self, desc: StaticDescriptor) -> Result<Self, BuilderError> {
+        check_section(Section::SendStatics, self.num_send_statics + 1)?;
+        self.send_statics[self.num_send_statics] = desc;
+        self.num_send_statics += 1;
+        self.checked()
+    }
+
+    /// Adds a send buffer/InMapAlias to this command (max 15)
+    ///
+    /// # Failures
+    /// * The send buffers are full
+    /// * The command no longer fits in the TLS
+    pub fn try_with_send_buffer(mut self, desc: BufferDescriptor) -> Result<Self, BuilderError> {
+        check_section(Section::SendBuffers, self.num_send_buffers + 1)?;
+        self.send_buffers[self.num_send_buffers] = desc;
+        self.num_send_buffers += 1;
+        self.checked()
+    }
+
+    /// Adds a receive buffer/OutMapAlias to this command (max 15)
+    ///
+    /// # Failures
+    /// * The receive buffers are full
+    /// * The command no longer fits in the TLS
+    pub fn try_with_recv_buffer(mut self, desc: BufferDescriptor) -> Result<Self, BuilderError> {
+        check_section(Section::RecvBuffers, self.num_recv_buffers + 1)?;
+        self.recv_buffers[self.num_recv_buffers] = desc;
+        self.num_recv_buffers += 1;
+        self.checked()
+    }
+
+    /// Adds an exchange buffer/InOutMapAlias to this command (max 15)
+    ///
+    /// # Failures
+    /// * The exchange buffers are full
+    /// * The command no longer fits in the TLS
+    pub fn try_with_exch_buffer(mut self, desc: BufferDescriptor) -> Result<Self, BuilderError> {
+        check_section(Section::ExchBuffers, self.num_exch_buffers + 1)?;
+        self.exch_buffers[self.num_exch_buffers] = desc;
+        self.num_exch_buffers += 1;
+        self.checked()
+    }
+
+    /// Adds a receive static/OutPointer to this command (max 13)
+    ///
+    /// # Failures
+    /// * The receive statics are full
+    /// * The receive list is already used by a pointer buffer or an inline buffer
+    /// * The command no longer fits in the TLS
+    pub fn try_with_recv_static(mut self, entry: ReceiveListEntry) -> Result<Self, BuilderError> {
+        check_section(Section::RecvStatics, self.num_recv_statics + 1)?;
+        self.recv_statics[self.num_recv_statics] = entry;
+        self.num_recv_statics += 1;
+        self.checked()
+    }
+
+    /// Adds a pointer buffer to this command (max 1)
+    ///
+    /// # Failures
+    /// * The command already has a pointer buffer
+    /// * The receive list is already used by receive statics or an inline buffer
+    /// * The command no longer fits in the TLS
+    pub fn try_with_pointer_buffer(mut self, entry: ReceiveListEntry) -> Result<Self, BuilderError> {
+        check_section(Section::PointerBuffer, self.pointer_buffer.is_some() as usize + 1)?;
+        self.pointer_buffer = Some(entry);
+        self.checked()
+    }
+
+    /// Sets the inline buffer of this command, replacing any that was already set
+    ///
+    /// # Failures
+    /// * The receive list is already used by receive statics or a pointer buffer
+    /// * The command no longer fits in the TLS
+    pub fn try_with_inline_buffer(mut self, buffer: &[u8]) -> Result<Self, BuilderError> {
+        if buffer.len() > self.inline_buffer.len() {
+            return Err(BuilderError::TlsOverflow { needed: buffer.len(), max: MAX_TLS_BUFFER_SIZE });
+        }
+
+        self.inline_buffer[..buffer.len()].copy_from_slice(buffer);
+        self.inline_buffer_len = buffer.len();
+        self.checked()
+    }
+
+    /// Sends a process ID in the special header, replacing any that was already set
+    ///
+    /// # Failures
+    /// * The command no longer fits in the TLS
+    pub fn try_with_process_id(mut self, process_id: u64) -> Result<Self, BuilderError> {
+        self.process_id = Some(process_id);
+        self.checked()
+    }
+
+    /// Adds a copy handle to the special header (max 15)
+    ///
+    /// # Failures
+    /// * The special header already holds 15 copy handles
+    /// * The command no longer fits in the TLS
+    pub fn try_with_copy_handle(self, handle: u32) -> Result<Self, BuilderError> {
+        self.try_with_handle(Handle::new(handle), HandleKind::Copy)
+    }
+
+    /// Adds a move handle to the special header (max 15)
+    ///
+    /// # Failures
+    /// * The special header already holds 15 move handles
+    /// * The command no longer fits in the TLS
+    pub fn try_with_move_handle(self, handle: u32) -> Result<Self, BuilderError> {
+        self.try_with_handle(Handle::new(handle), HandleKind::Move)
+    }
+
+    /// Adds a handle to the special header as either a copy or a move handle (max 15 of each)
+    ///
+    /// This lets handles of both kinds be kept in one list and added in a single loop.
+    ///
+    /// # Failures
+    /// * The special header already holds 15 handles of that kind
+    /// * The command no longer fits in the TLS
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, runtime::TryHipcCommandBuilder}, header::{self, Handle, HandleKind}, CommandType};
+    ///
+    /// let handles = [
+    ///     (Handle::new(0x1234), HandleKind::Move),
+    ///     (Handle::new(0xFFFF_8001), HandleKind::Copy),
+    ///     (Handle::new(0x5678), HandleKind::Move)
+    /// ];
+    ///
+    /// let mut builder = TryHipcCommandBuilder::new(CommandType::Request);
+    /// for (handle, kind) in handles {
+    ///     builder = builder.try_with_handle(handle, kind).unwrap();
+    /// }
+    ///
+    /// let mut out = [0u8; 0x100];
+    /// let len = builder.build_into(&mut out).unwrap();
+    ///
+    /// // Copy handles are always serialized before move handles
+    /// let expected = command::request_builder()
+    ///     .with_special_header(header::new_builder()
+    ///         .with_copy_handle(0xFFFF_8001)
+    ///         .with_move_handle(0x1234)
+    ///         .with_move_handle(0x5678))
+    ///     .build();
+    /// assert_eq!(&out[..len], &expected);
+    /// ```
+    pub fn try_with_handle(mut self, handle: Handle, kind: HandleKind) -> Result<Self, BuilderError> {
+        let (handles, count): (&mut [u32], _) = match kind {
+            HandleKind::Copy => (&mut self.copy_handles, &mut self.num_copy_handles),
+            HandleKind::Move => (&mut self.move_handles, &mut self.num_move_handles)
+        };
+
+        if *count == handles.len() {
+            return Err(BuilderError::TooManyHandles { max: handles.len() });
+        }
+
+        handles[*count] = handle.raw();
+        *count += 1;
+        self.checked()
+    }
+
+    /// Sets the raw data payload of this command, replacing any that was already set
+    ///
+    /// # Failures
+    /// * The same situations as [`TryHipcCommandBuilder::set_raw_words`]
+    pub fn try_with_raw_data(mut self, data: &[u32]) -> Result<Self, BuilderError> {
+        self.set_raw_words(data)?;
+        Ok(self)
+    }
+
+    /// Appends words to the raw data payload
+    ///
+    /// This builds up a payload whose length is only known at runtime, such as a CMIF request
+    /// with a variable number of arguments.
+    ///
+    /// # Failures
+    /// * The raw data would be longer than the header can describe (0x3FF words)
+    /// * The command no longer fits in the TLS
+    ///
+    /// Nothing is appended when this fails.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{self, runtime::TryHipcCommandBuilder, BuilderError}, CommandType};
+    ///
+    /// fn with_args(args: &[u32]) -> Result<TryHipcCommandBuilder, BuilderError> {
+    ///     let mut builder = TryHipcCommandBuilder::new(CommandType::Request);
+    ///     builder.set_raw_words(&[42])?;
+    ///     for chunk in args.chunks(3) {
+    ///         builder.push_raw_words(chunk)?;
+    ///     }
+    ///     Ok(builder)
+    /// }
+    ///
+    /// for len in [0, 1, 5, 61] {
+    ///     let args: Vec<u32> = (0..len).collect();
+    ///     let mut out = [0u8; 0x100];
+    ///     let written = with_args(&args).unwrap().build_into(&mut out).unwrap();
+    ///
+    ///     let words: Vec<u32> = out[8..written].chunks(4).map(|word| u32::from_le_bytes(word.try_into().unwrap())).collect();
+    ///     assert_eq!(words[0], 42);
+    ///     assert_eq!(words[1..], args[..]);
+    /// }
+    ///
+    /// // 62 words fill the TLS, one more does not fit
+    /// assert_eq!(with_args(&[0; 62]).err(), Some(BuilderError::TlsOverflow { needed: 8 + 63 * 4, max: 0x100 }));
+    ///
+    /// // A failed push leaves the payload as it was
+    /// let mut builder = with_args(&[1, 2]).unwrap();
+    /// assert_eq!(builder.push_raw_words(&[0; 0x400]), Err(BuilderError::RawDataTooLong { len: 0x403, max: 0x3FF }));
+    /// assert_eq!(builder.len(), 8 + 3 * 4);
+    /// ```
+    pub fn push_raw_words(&mut self, words: &[u32]) -> Result<(), BuilderError> {
+        let start = self.raw_data_len;
+        self.store_raw_words(start, words)
+    }
+
+    /// Sets the raw data payload, replacing any that was already set
+    ///
+    /// # Failures
+    /// * The raw data is longer than the header can describe (0x3FF words)
+    /// * The command no longer fits in the TLS
+    ///
+    /// The payload is left unchanged when this fails.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command::{runtime::TryHipcCommandBuilder, BuilderError}, CommandType};
+    ///
+    /// let mut builder = TryHipcCommandBuilder::new(CommandType::Request);
+    /// builder.set_raw_words(&[1, 2, 3]).unwrap();
+    /// builder.set_raw_words(&[4]).unwrap();
+    /// assert_eq!(builder.len(), 8 + 4);
+    ///
+    /// assert_eq!(builder.set_raw_words(&[0; 0x400]), Err(BuilderError::RawDataTooLong { len: 0x400, max: 0x3FF }));
+    /// assert_eq!(builder.set_raw_words(&[0; 0x40]), Err(BuilderError::TlsOverflow { needed: 8 + 0x100, max: 0x100 }));
+    /// assert_eq!(builder.len(), 8 + 4);
+    /// ```
+    pub fn set_raw_words(&mut self, words: &[u32]) -> Result<(), BuilderError> {
+        self.store_raw_words(0, words)
+    }
+
+    /// The number of bytes the command serializes to
+    pub fn len(&self) -> usize {
+        section_offsets(
+            self.num_send_statics,
+            self.num_send_buffers,
+            self.num_recv_buffers,
+            self.num_exch_buffers,
+            self.num_recv_statics,
+            self.raw_data_len,
+            self.inline_buffer_len,
+            self.special_header_total(),
+            self.pointer_buffer.is_some()
+        ).end
+    }
+
+    /// Always `false`, a command contains at least its header
+    pub fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Serializes the command into the start of `out`
+    ///
+    /// The output is identical to what [`HipcCommandBuilder::build`](super::HipcCommandBuilder::build)
+    /// produces for a command of the same shape.
+    ///
+    /// # Returns
+    /// * The number of bytes written
+    ///
+    /// # Failures
+    /// * `out` is shorter than the command
+    /// * The same situations as [`check_context_token`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{cmif::{self, CmifMode}, command::{runtime::TryHipcCommandBuilder, BuilderError}, CommandType};
+    ///
+    /// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+    ///
+    /// let words: [u32; cmif::request_len(OFFSET, CmifMode::Session, 1, 0)] =
+    ///     cmif::request_words(OFFSET, CmifMode::Session, 42, [7], &[]);
+    /// let with_token = cmif::with_context_token(words, OFFSET, 0x1234);
+    ///
+    /// let mut out = [0xFFu8; 0x100];
+    /// let command = TryHipcCommandBuilder::new(CommandType::RequestWithContext).try_with_raw_data(&with_token).unwrap();
+    /// let len = command.build_into(&mut out).unwrap();
+    /// assert_eq!(&out[..len], &hipc::command::request_with_context_builder(0x1234).with_raw_data(words).build());
+    ///
+    /// // Nothing is written when the token does not match the command type
+    /// let mut out = [0xFFu8; 0x100];
+    /// let missing = TryHipcCommandBuilder::new(CommandType::RequestWithContext).try_with_raw_data(&words).unwrap();
+    /// assert_eq!(missing.build_into(&mut out), Err(BuilderError::MissingContextToken));
+    ///
+    /// let spurious = TryHipcCommandBuilder::new(CommandType::Request).try_with_raw_data(&with_token).unwrap();
+    /// assert_eq!(spurious.build_into(&mut out), Err(BuilderError::UnexpectedContextToken));
+    /// assert!(out.iter().all(|byte| *byte == 0xFF));
+    /// ```
+    pub fn build_into(&self, out: &mut [u8]) -> Result<usize, BuilderError> {
+        let len = self.len();
+        if out.len() < len {
+            return Err(BuilderError::BufferTooSmall { needed: len, available: out.len() });
+        }
+
+        let offsets = section_offsets(
+            self.num_send_statics,
+            self.num_send_buffers,
+            self.num_recv_buffers,
+            self.num_exch_buffers,
+            self.num_recv_statics,
+            self.raw_data_len,
+            self.inline_buffer_len,
+            self.special_header_total(),
+            self.pointer_buffer.is_some()
+        );
+
+        check_context_token(self.ty, &self.raw_data[..self.raw_data_len], offsets.raw_data)?;
+
+        let out = &mut out[..len];
+        out.fill(0);
+
+        let header = Header::new(
+            self.ty as u16,
+            self.num_send_statics,
+            self.num_send_buffers,
+            self.num_recv_buffers,
+            self.num_exch_buffers,
+            self.raw_data_len,
+            helpers::get_recv_mode(self.num_recv_statics, self.inline_buffer_len, self.pointer_buffer.is_some()),
+            offsets.receive_list_offset(),
+            self.has_special_header()
+        );
+        write(out, offsets.header, &<[u8; 8]>::from(header));
+
+        if self.has_special_header() {
+            let mut cursor = offsets.special_header;
+            let special_header = SpecialHeader::new(self.process_id.is_some(), self.num_copy_handles, self.num_move_handles);
+            cursor = write(out, cursor, &<[u8; 4]>::from(special_header));
+
+            if let Some(process_id) = self.process_id {
+                cursor = write(out, cursor, &process_id.to_le_bytes());
+            }
+
+            for handle in self.copy_handles[..self.num_copy_handles].iter().chain(&self.move_handles[..self.num_move_handles]) {
+                cursor = write(out, cursor, &handle.to_le_bytes());
+            }
+        }
+
+        let mut cursor = offsets.send_statics;
+        for desc in &self.send_statics[..self.num_send_statics] {
+            cursor = write(out, cursor, &<[u8; 8]>::from(*desc));
+        }
+
+        let buffers = self.send_buffers[..self.num_send_buffers].iter()
+            .chain(&self.recv_buffers[..self.num_recv_buffers])
+            .chain(&self.exch_buffers[..self.num_exch_buffers]);
+
+        let mut cursor = offsets.send_buffers;
+        for desc in buffers {
+            cursor = write(out, cursor, &<[u8; 12]>::from(*desc));
+        }
+
+        let mut cursor = offsets.raw_data;
+        for word in &self.raw_data[..self.raw_data_len] {
+            cursor = write(out, cursor, &word.to_le_bytes());
+        }
+
+        write(out, offsets.inline_buffer, &self.inline_buffer[..self.inline_buffer_len]);
+
+        if let Some(entry) = self.pointer_buffer {
+            write(out, offsets.pointer_buffer, &<[u8; 8]>::from(entry));
+        }
+
+        let mut cursor = offsets.recv_statics;
+        for entry in &self.recv_statics[..self.num_recv_statics] {
+            cursor = write(out, cursor, &<[u8; 8]>::from(*entry));
+        }
+
+        Ok(len)
+    }
+
+    /// If the command needs a special header
+    fn has_special_header(&self) -> bool {
+        self.process_id.is_some() || self.num_copy_handles != 0 || self.num_move_handles != 0
+    }
+
+    /// The number of bytes the special header consumes, 0 if there is none
+    fn special_header_total(&self) -> usize {
+        if self.has_special_header() {
+            consumed_space(self.process_id.is_some() as usize, self.num_copy_handles, self.num_move_handles)
+        } else {
+            0
+        }
+    }
+
+    /// Checks the layout of the command as if it had `raw_len` words of raw data
+    fn layout_with_raw_data(&self, raw_len: usize) -> Result<usize, BuilderError> {
+        check_layout(
+            self.num_send_statics,
+            self.num_send_buffers,
+            self.num_recv_buffers,
+            self.num_exch_buffers,
+            self.num_recv_statics,
+            raw_len,
+            self.inline_buffer_len,
+            self.special_header_total(),
+            self.pointer_buffer.is_some()
+        )
+    }
+
+    /// Writes `words` into the raw data at `start` and ends the raw data after them
+    fn store_raw_words(&mut self, start: usize, words: &[u32]) -> Result<(), BuilderError> {
+        let len = start + words.len();
+        if len > MAX_RAW_DATA_WORDS {
+            return Err(BuilderError::RawDataTooLong { len, max: MAX_RAW_DATA_WORDS });
+        }
+
+        let needed = self.layout_with_raw_data(len)?;
+        if len > self.raw_data.len() {
+            return Err(BuilderError::TlsOverflow { needed, max: MAX_TLS_BUFFER_SIZE });
+        }
+
+        self.raw_data[start..len].copy_from_slice(words);
+        self.raw_data_len = len;
+        Ok(())
+    }
+
+    /// Returns the builder if its layout is still legal
+    fn checked(self) -> Result<Self, BuilderError> {
+        self.layout_with_raw_data(self.raw_data_len)?;
+        Ok(self)
+    }
+}
+
+/// Copies `bytes` into `out` at `offset`
+///
+/// # Returns
+/// * The offset just past the copied bytes
+fn write(out: &mut [u8], offset: usize, bytes: &[u8]) -> usize {
+    out[offset..offset + bytes.len()].copy_from_slice(bytes);
+    offset + bytes.len()
+}
@@ -0,0 +1,184 @@
+use crate::{cmif::{self, CmifError}, command, header::SpecialHeaderView, packed::Header, raw_data, CommandType};
+
+/// The control command ID for `CopyFromCurrentDomain`
+pub const COPY_FROM_CURRENT_DOMAIN: u32 = 1;
+
+/// The control command ID for `CloneCurrentObjectEx`
+pub const CLONE_CURRENT_OBJECT_EX: u32 = 4;
+
+/// The byte offset of the raw data in a control request, which carries no descriptors
+const REQUEST_RAW_DATA_OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+
+/// Builds the CMIF header for a control request
+const fn request_header(command_id: u32) -> [u32; 4] {
+    [cmif::REQUEST_MAGIC, 0, command_id, 0]
+}
+
+/// Builds a `CopyFromCurrentDomain` control request
+///
+/// The server responds with a move handle to a new session for the domain object. The raw data
+/// carries the CMIF header followed by the object ID as a single word.
+///
+/// # Arguments
+/// * `object_id` - The ID of the domain object to open a session for
+///
+/// # Example
+/// ```
+/// use hipc::{cmif, control};
+///
+/// let bytes = control::copy_from_domain(3);
+///
+/// // A control command with 7 words of raw data
+/// assert_eq!(&bytes[..8], &[0x05, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00]);
+/// assert_eq!(cmif::request_command_id(&bytes[8..], 8), Ok(control::COPY_FROM_CURRENT_DOMAIN));
+/// assert_eq!(&bytes[0x20..], &3u32.to_le_bytes());
+/// ```
+pub const fn copy_from_domain(object_id: u32) -> [u8; 36] {
+    command::new_builder(CommandType::Control)
+        .with_raw_data(
+            raw_data::new_builder::<REQUEST_RAW_DATA_OFFSET>()
+                .with_header(request_header(COPY_FROM_CURRENT_DOMAIN))
+                .with_args([object_id])
+        )
+        .build()
+}
+
+/// Extracts the first move handle from a control command's response, checking its result code
+///
+/// # Failures
+/// * The response is too short for the sections its headers declare
+/// * The response carries no move handles
+/// * The CMIF header is malformed or carries a failing result code
+const fn parse_move_handle_response(response: &[u8]) -> Result<u32, CmifError> {
+    if response.len() < 12 {
+        return Err(CmifError::Truncated);
+    }
+
+    let header = Header::from_bytes([
+        response[0], response[1], response[2], response[3],
+        response[4], response[5], response[6], response[7]
+    ]);
+    if !header.has_special_header() {
+        return Err(CmifError::MissingHandle);
+    }
+
+    let (_, special_header) = response.split_at(8);
+    let special_header = match SpecialHeaderView::from_bytes(special_header) {
+        Ok(view) => view,
+        Err(_) => return Err(CmifError::Truncated)
+    };
+
+    let handle = match special_header.move_handle(0) {
+        Some(handle) => handle,
+        None => return Err(CmifError::MissingHandle)
+    };
+
+    let mode = header.receive_static_mode() as usize;
+    let offsets = command::section_offsets(
+        header.num_send_statics(),
+        header.num_send_buffers(),
+        header.num_receive_buffers(),
+        header.num_exchange_buffers(),
+        mode.saturating_sub(2),
+        header.raw_data_len(),
+        0,
+        special_header.byte_len(),
+        mode == 2
+    );
+
+    if response.len() < header.total_len(special_header.byte_len()) {
+        return Err(CmifError::Truncated);
+    }
+
+    let (_, raw) = response.split_at(offsets.raw_data);
+
+    if let Err(err) = cmif::response_result(raw, offsets.raw_data) {
+        return Err(err);
+    }
+
+    Ok(handle)
+}
+
+/// Extracts the session handle from the response to a [`copy_from_domain`] request
+///
+/// # Failures
+/// * The response is truncated
+/// * The response carries no move handle
+/// * The CMIF header is malformed or carries a failing result code
+///
+/// # Example
+/// ```
+/// use hipc::{cmif::{self, CmifError}, command, control, header, packed::ReceiveListEntry, raw_data, CommandType};
+///
+/// let response = command::new_builder(CommandType::Invalid)
+///     .with_special_header(header::new_builder().with_move_handle(0xABCD))
+///     .with_raw_data(raw_data::new_builder::<0x10>().with_header([cmif::RESPONSE_MAGIC, 0, 0, 0]))
+///     .build();
+///
+/// assert_eq!(control::copy_from_domain_response(&response), Ok(0xABCD));
+///
+/// // The receive list after the raw data is part of the response too
+/// let response = command::new_builder(CommandType::Invalid)
+///     .with_special_header(header::new_builder().with_move_handle(0xABCD))
+///     .with_raw_data(raw_data::new_builder::<0x10>().with_header([cmif::RESPONSE_MAGIC, 0, 0, 0]))
+///     .with_recv_static(ReceiveListEntry::new(0x1000, 0x100))
+///     .build();
+///
+/// assert_eq!(control::copy_from_domain_response(&response), Ok(0xABCD));
+/// assert_eq!(control::copy_from_domain_response(&response[..response.len() - 4]), Err(CmifError::Truncated));
+/// ```
+pub const fn copy_from_domain_response(response: &[u8]) -> Result<u32, CmifError> {
+    parse_move_handle_response(response)
+}
+
+/// Builds a `CloneCurrentObjectEx` control request
+///
+/// The server responds with a move handle to a new session for the same object. The raw data
+/// carries the CMIF header followed by the tag as a single word.
+///
+/// # Arguments
+/// * `tag` - A value passed through to the service, whose meaning is service-specific
+///
+/// # Example
+/// ```
+/// use hipc::{cmif, control};
+///
+/// let bytes = control::clone_current_object_ex(0xCAFE);
+///
+/// // A control command with 7 words of raw data
+/// assert_eq!(&bytes[..8], &[0x05, 0x00, 0x00, 0x00, 0x07, 0x00, 0x00, 0x00]);
+/// assert_eq!(cmif::request_command_id(&bytes[8..], 8), Ok(control::CLONE_CURRENT_OBJECT_EX));
+/// assert_eq!(&bytes[0x20..], &0xCAFEu32.to_le_bytes());
+/// ```
+pub const fn clone_current_object_ex(tag: u32) -> [u8; 36] {
+    command::new_builder(CommandType::Control)
+        .with_raw_data(
+            raw_data::new_builder::<REQUEST_RAW_DATA_OFFSET>()
+                .with_header(request_header(CLONE_CURRENT_OBJECT_EX))
+                .with_args([tag])
+        )
+        .build()
+}
+
+/// Extracts the session handle from the response to a [`clone_current_object_ex`] request
+///
+/// # Failures
+/// * The response is truncated
+/// * The response carries no move handle
+/// * The CMIF header is malformed or carries a failing result code
+///
+/// # Example
+/// ```
+/// use hipc::{cmif::{self, CmifError}, command, control, header, raw_data, CommandType};
+///
+/// let response = |result: u32| command::new_builder(CommandType::Invalid)
+///     .with_special_header(header::new_builder().with_move_handle(0x1234))
+///     .with_raw_data(raw_data::new_builder::<0x10>().with_header([cmif::RESPONSE_MAGIC, 0, result, 0]))
+///     .build();
+///
+/// assert_eq!(control::clone_current_object_ex_response(&response(0)), Ok(0x1234));
+/// assert_eq!(control::clone_current_object_ex_response(&response(0x1A8)), Err(CmifError::Failure(0x1A8)));
+/// ```
+pub const fn clone_current_object_ex_response(response: &[u8]) -> Result<u32, CmifError> {
+    parse_move_handle_response(response)
+}
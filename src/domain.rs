@@ -0,0 +1,317 @@
+use crate::{cmif::DOMAIN_HEADER_SIZE, util::read_word, ParseError};
+
+/// The operation a domain request performs on its target object
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DomainCommandKind {
+    /// Sends the CMIF request following the domain header to the object
+    SendMessage = 0x1,
+
+    /// Closes the object, releasing its ID
+    Close = 0x2
+}
+
+/// The header which precedes the CMIF header in the raw data of a domain request
+///
+/// # Memory Layout
+/// | 32-bit Word | Purpose |
+/// | ----------- | ------- |
+/// | `0` | Command kind (bits `0..8`), input object count (bits `8..16`), data length (bits `16..32`) |
+/// | `1` | Target object ID |
+/// | `2` | Padding |
+/// | `3` | Token |
+///
+/// # Example
+/// ```
+/// use hipc::domain::{DomainCommandKind, DomainRequestHeader};
+///
+/// let header = DomainRequestHeader::new(DomainCommandKind::SendMessage, 3, 0x18, 0xABCD);
+/// assert_eq!(header.kind(), DomainCommandKind::SendMessage as u8);
+/// assert_eq!(header.num_in_objects(), 3);
+/// assert_eq!(header.data_len(), 0x18);
+/// assert_eq!(header.object_id(), 0xABCD);
+/// assert_eq!(header.token(), 0);
+/// assert_eq!(header.words(), [0x0018_0301, 0xABCD, 0, 0]);
+///
+/// // Serializing and reading the header back gives the same fields
+/// let bytes: [u8; 16] = header.into();
+/// assert_eq!(&bytes[..8], &[0x01, 0x03, 0x18, 0x00, 0xCD, 0xAB, 0x00, 0x00]);
+/// assert_eq!(DomainRequestHeader::from_bytes(bytes), header);
+/// assert_eq!(DomainRequestHeader::from(bytes), header);
+///
+/// // Fields wider than their bits are truncated
+/// let header = DomainRequestHeader::new(DomainCommandKind::Close, 0x1FF, 0x1_0010, 1);
+/// assert_eq!(header.kind(), DomainCommandKind::Close as u8);
+/// assert_eq!(header.num_in_objects(), 0xFF);
+/// assert_eq!(header.data_len(), 0x10);
+/// ```
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct DomainRequestHeader([u32; 4]);
+
+impl DomainRequestHeader {
+    /// The raw command kind, see [`DomainCommandKind`]
+    pub const fn kind(self) -> u8 {
+        self.0[0] as u8
+    }
+
+    /// The number of input object IDs at the end of the request
+    pub const fn num_in_objects(self) -> usize {
+        ((self.0[0] >> 8) & 0xFF) as usize
+    }
+
+    /// The number of bytes of CMIF data between the domain header and the input object IDs
+    pub const fn data_len(self) -> usize {
+        (self.0[0] >> 16) as usize
+    }
+
+    /// The ID of the object the request targets
+    pub const fn object_id(self) -> u32 {
+        self.0[1]
+    }
+
+    /// The token carried in the last word of the header
+    pub const fn token(self) -> u32 {
+        self.0[3]
+    }
+
+    /// The header words exactly as they are serialized
+    pub const fn words(self) -> [u32; 4] {
+        self.0
+    }
+
+    /// Constructs a domain request header, with the padding and token words zeroed
+    pub const fn new(kind: DomainCommandKind, num_in_objects: usize, data_len: usize, object_id: u32) -> Self {
+        let first = (kind as u32) | ((num_in_objects as u32 & 0xFF) << 8) | ((data_len as u32 & 0xFFFF) << 16);
+        Self([first, object_id, 0, 0])
+    }
+
+    /// Reads a header back out of its serialized bytes
+    pub const fn from_bytes(bytes: [u8; DOMAIN_HEADER_SIZE]) -> Self {
+        Self([read_word(&bytes, 0), read_word(&bytes, 4), read_word(&bytes, 8), read_word(&bytes, 12)])
+    }
+}
+
+impl const From<[u8; DOMAIN_HEADER_SIZE]> for DomainRequestHeader {
+    fn from(value: [u8; DOMAIN_HEADER_SIZE]) -> Self {
+        Self::from_bytes(value)
+    }
+}
+
+impl const From<DomainRequestHeader> for [u8; DOMAIN_HEADER_SIZE] {
+    fn from(value: DomainRequestHeader) -> Self {
+        let mut out = [0u8; DOMAIN_HEADER_SIZE];
+
+        let mut word = 0;
+        while word < 4 {
+            let bytes = value.0[word].to_le_bytes();
+            let mut index = 0;
+            while index < 4 {
+                out[word * 4 + index] = bytes[index];
+                index += 1;
+            }
+            word += 1;
+        }
+
+        out
+    }
+}
+
+/// The header which precedes the CMIF header in the raw data of a domain response
+///
+/// # Memory Layout
+/// | 32-bit Word | Purpose |
+/// | ----------- | ------- |
+/// | `0` | Output object count |
+/// | `1..4` | Padding |
+///
+/// # Example
+/// ```
+/// use hipc::domain::DomainResponseHeader;
+///
+/// let header = DomainResponseHeader::new(2);
+/// assert_eq!(header.num_out_objects(), 2);
+/// assert_eq!(header.words(), [2, 0, 0, 0]);
+///
+/// let bytes: [u8; 16] = header.into();
+/// assert_eq!(DomainResponseHeader::from_bytes(bytes), header);
+/// ```
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub struct DomainResponseHeader([u32; 4]);
+
+impl DomainResponseHeader {
+    /// The number of output object IDs at the end of the response
+    pub const fn num_out_objects(self) -> usize {
+        self.0[0] as usize
+    }
+
+    /// The header words exactly as they are serialized
+    pub const fn words(self) -> [u32; 4] {
+        self.0
+    }
+
+    /// Constructs a domain response header, with the padding words zeroed
+    pub const fn new(num_out_objects: usize) -> Self {
+        Self([num_out_objects as u32, 0, 0, 0])
+    }
+
+    /// Reads a header back out of its serialized bytes
+    pub const fn from_bytes(bytes: [u8; DOMAIN_HEADER_SIZE]) -> Self {
+        Self([read_word(&bytes, 0), read_word(&bytes, 4), read_word(&bytes, 8), read_word(&bytes, 12)])
+    }
+}
+
+impl const From<[u8; DOMAIN_HEADER_SIZE]> for DomainResponseHeader {
+    fn from(value: [u8; DOMAIN_HEADER_SIZE]) -> Self {
+        Self::from_bytes(value)
+    }
+}
+
+impl const From<DomainResponseHeader> for [u8; DOMAIN_HEADER_SIZE] {
+    fn from(value: DomainResponseHeader) -> Self {
+        let mut out = [0u8; DOMAIN_HEADER_SIZE];
+
+        let mut word = 0;
+        while word < 4 {
+            let bytes = value.0[word].to_le_bytes();
+            let mut index = 0;
+            while index < 4 {
+                out[word * 4 + index] = bytes[index];
+                index += 1;
+            }
+            word += 1;
+        }
+
+        out
+    }
+}
+
+/// The object IDs of a domain request or response, read out of the borrowed raw data
+#[derive(Copy, Clone, Debug)]
+pub struct ObjectIds<'a> {
+    bytes: &'a [u8]
+}
+
+impl<'a> ObjectIds<'a> {
+    /// The number of object IDs
+    pub const fn len(&self) -> usize {
+        self.bytes.len() / core::mem::size_of::<u32>()
+    }
+
+    /// If there are no object IDs
+    pub const fn is_empty(&self) -> bool {
+        self.bytes.is_empty()
+    }
+
+    /// The object ID at `index`, if there is one
+    pub const fn get(&self, index: usize) -> Option<u32> {
+        if index >= self.len() {
+            return None;
+        }
+
+        Some(read_word(self.bytes, index * core::mem::size_of::<u32>()))
+    }
+
+    /// Iterates over the object IDs, in order
+    pub fn iter(&self) -> impl Iterator<Item = u32> + 'a {
+        self.bytes.chunks_exact(4).map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+    }
+}
+
+/// Extracts the input object IDs from a domain request
+///
+/// The object IDs follow the `data_len` bytes of CMIF data after the domain header.
+///
+/// # Arguments
+/// * `raw` - The bytes directly following the domain header
+/// * `header` - The domain header of the request
+///
+/// # Failures
+/// * `raw` is too short for the CMIF data and object IDs the header declares
+///
+/// # Example
+/// ```
+/// use hipc::{cmif, domain::{self, DomainCommandKind, DomainRequestHeader}, ParseError};
+///
+/// // 16 bytes of CMIF header and 4 bytes of arguments, followed by two object IDs
+/// let header = DomainRequestHeader::new(DomainCommandKind::SendMessage, 2, 0x14, 0x10);
+///
+/// let mut raw = [0u8; 0x1C];
+/// raw[0x00..0x04].copy_from_slice(&cmif::REQUEST_MAGIC.to_le_bytes());
+/// raw[0x14..0x18].copy_from_slice(&0x21u32.to_le_bytes());
+/// raw[0x18..0x1C].copy_from_slice(&0x22u32.to_le_bytes());
+///
+/// let ids = domain::domain_in_object_ids(&raw, &header).unwrap();
+/// assert_eq!(ids.len(), 2);
+/// assert_eq!(ids.get(1), Some(0x22));
+/// assert!(ids.iter().eq([0x21, 0x22]));
+///
+/// assert_eq!(
+///     domain::domain_in_object_ids(&raw[..0x18], &header).unwrap_err(),
+///     ParseError::TruncatedObjectIds { needed: 0x1C, available: 0x18 }
+/// );
+/// ```
+pub const fn domain_in_object_ids<'a>(raw: &'a [u8], header: &DomainRequestHeader) -> Result<ObjectIds<'a>, ParseError> {
+    let start = header.data_len();
+    let needed = start + header.num_in_objects() * core::mem::size_of::<u32>();
+
+    if raw.len() < needed {
+        return Err(ParseError::TruncatedObjectIds { needed, available: raw.len() });
+    }
+
+    let (bytes, _) = raw.split_at(needed);
+    let (_, bytes) = bytes.split_at(start);
+
+    Ok(ObjectIds { bytes })
+}
+
+/// Extracts the output object IDs from a domain response
+///
+/// The object IDs follow the `data_len` bytes of CMIF response data after the domain header.
+/// The response header does not record the length of that data, so it has to come from the
+/// command that was sent.
+///
+/// # Arguments
+/// * `raw` - The bytes directly following the domain header
+/// * `header` - The domain header of the response
+/// * `data_len` - The number of bytes of CMIF header and output data before the object IDs
+///
+/// # Failures
+/// * `raw` is too short for the CMIF data and object IDs the header declares
+///
+/// # Example
+/// ```
+/// use hipc::{cmif, domain::{self, DomainResponseHeader}, ParseError};
+///
+/// // 16 bytes of CMIF header and 4 bytes of output, followed by two object IDs
+/// let mut response = [0u8; 0x10 + 0x1C];
+/// response[..0x10].copy_from_slice(&<[u8; 16]>::from(DomainResponseHeader::new(2)));
+/// response[0x10..0x14].copy_from_slice(&cmif::RESPONSE_MAGIC.to_le_bytes());
+/// response[0x24..0x28].copy_from_slice(&0x31u32.to_le_bytes());
+/// response[0x28..0x2C].copy_from_slice(&0x32u32.to_le_bytes());
+///
+/// let header = DomainResponseHeader::from_bytes(response[..0x10].try_into().unwrap());
+/// let raw = &response[0x10..];
+///
+/// let ids = domain::domain_out_object_ids(raw, &header, 0x14).unwrap();
+/// assert_eq!(ids.len(), 2);
+/// assert!(ids.iter().eq([0x31, 0x32]));
+///
+/// assert_eq!(
+///     domain::domain_out_object_ids(&raw[..0x18], &header, 0x14).unwrap_err(),
+///     ParseError::TruncatedObjectIds { needed: 0x1C, available: 0x18 }
+/// );
+/// ```
+pub const fn domain_out_object_ids<'a>(raw: &'a [u8], header: &DomainResponseHeader, data_len: usize) -> Result<ObjectIds<'a>, ParseError> {
+    let needed = data_len + header.num_out_objects() * core::mem::size_of::<u32>();
+
+    if raw.len() < needed {
+        return Err(ParseError::TruncatedObjectIds { needed, available: raw.len() });
+    }
+
+    let (bytes, _) = raw.split_at(needed);
+    let (_, bytes) = bytes.split_at(data_len);
+
+    Ok(ObjectIds { bytes })
+}
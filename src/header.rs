@@ -7,13 +7,38 @@
 ///       value and following the same format as other values is ideal.
 const MAX_PIDS: usize = 0x01;
 
+/// Size, in bytes, of the process ID carried in the special header
+///
+/// The special header word only records whether a process ID is present, so readers must
+/// know to consume exactly this many bytes after it. The process ID is always the full
+/// 64-bit value, never a 32-bit one.
+///
+/// # Example
+/// ```
+/// use hipc::header;
+///
+/// let bytes = header::new_builder()
+///     .with_program_id(0x0123_4567_89AB_CDEF)
+///     .build();
+///
+/// assert_eq!(bytes.len(), 4 + header::PROCESS_ID_SIZE);
+///
+/// let mut pid = [0u8; header::PROCESS_ID_SIZE];
+/// pid.copy_from_slice(&bytes[4..]);
+/// assert_eq!(u64::from_le_bytes(pid), 0x0123_4567_89AB_CDEF);
+/// ```
+pub const PROCESS_ID_SIZE: usize = core::mem::size_of::<u64>();
+
+// The handles following the process ID must stay word aligned
+const _: () = assert!(PROCESS_ID_SIZE % core::mem::size_of::<u32>() == 0);
+
 /// Maximum amount of copy handles which can be provided through the special header
-const MAX_COPY: usize = 0x0F;
+pub(crate) const MAX_COPY: usize = 0x0F;
 
 /// Maximum amount of move handles which can be provided through the special header
-const MAX_MOVE: usize = 0x0F;
+pub(crate) const MAX_MOVE: usize = 0x0F;
 
-use crate::command::helpers;
+use crate::{command::helpers, util::const_assert};
 
 /// Helper macro for creating a [`SpecialHeaderBuilder`] type
 /// 
@@ -34,6 +59,57 @@ macro_rules! header_ty {
 
 pub use header_ty;
 
+/// A kernel handle to be sent in the special header
+///
+/// The raw value is the same whether the handle is copied or moved, see [`HandleKind`].
+///
+/// # Example
+/// ```
+/// use hipc::header::Handle;
+///
+/// let handle = Handle::new(0xFFFF_8001);
+/// assert_eq!(handle.raw(), 0xFFFF_8001);
+/// assert_eq!(Handle::from(0xFFFF_8001), handle);
+/// assert_eq!(u32::from(handle), 0xFFFF_8001);
+/// ```
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Handle(u32);
+
+impl Handle {
+    /// Wraps a raw handle value
+    pub const fn new(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    /// The raw handle value
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+}
+
+impl const From<u32> for Handle {
+    fn from(value: u32) -> Self {
+        Self(value)
+    }
+}
+
+impl const From<Handle> for u32 {
+    fn from(value: Handle) -> Self {
+        value.0
+    }
+}
+
+/// How the kernel transfers a handle sent in the special header
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum HandleKind {
+    /// The receiver gets a new handle to the same object, the sender keeps theirs
+    Copy,
+
+    /// The handle is closed for the sender and given to the receiver
+    Move
+}
+
 /// Builder for the optional special header in HIPC commands
 /// 
 /// # Generics
@@ -59,6 +135,33 @@ pub use header_ty;
 /// }
 /// ```
 /// 
+/// # Reuse
+/// The builder is `Copy`, so a header can be built once as a `const` and passed to
+/// [`with_special_header`](crate::command::HipcCommandBuilder::with_special_header) on as many
+/// commands as needed.
+/// ```
+/// use hipc::{command, header, packed::BufferDescriptor};
+///
+/// const CURRENT_PROCESS: header::header_ty!(false, 1, 0) = header::new_builder()
+///     .with_copy_handle(0xFFFF_8001);
+///
+/// let first = command::request_builder()
+///     .with_special_header(CURRENT_PROCESS)
+///     .build();
+/// let second = command::request_builder()
+///     .with_special_header(CURRENT_PROCESS)
+///     .with_raw_data([1u32])
+///     .build();
+/// let third = command::control_builder()
+///     .with_special_header(CURRENT_PROCESS)
+///     .with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+///     .build();
+///
+/// for bytes in [&first[..], &second[..], &third[..]] {
+///     assert_eq!(&bytes[8..16], &CURRENT_PROCESS.build());
+/// }
+/// ```
+///
 /// # Memory Layout
 /// Once serialized to bytes, the special header contains the following layout:
 /// 
@@ -78,22 +181,43 @@ pub struct SpecialHeaderBuilder<const PIDS: usize, const CP: usize, const MV: us
 
 #[doc(hidden)]
 pub const fn consumed_space(pids: usize, copy: usize, move_: usize) -> usize {
-    pids * core::mem::size_of::<u64>() + copy * core::mem::size_of::<u32>() + move_ * core::mem::size_of::<u32>() + core::mem::size_of::<crate::packed::SpecialHeader>()
+    pids * PROCESS_ID_SIZE + copy * core::mem::size_of::<u32>() + move_ * core::mem::size_of::<u32>() + core::mem::size_of::<crate::packed::SpecialHeader>()
 }
 
-impl<const PIDS: usize, const CP: usize, const MV: usize, const TOTAL: usize> SpecialHeaderBuilder<PIDS, CP, MV, TOTAL> {
+impl SpecialHeaderBuilder<0, 0, 0, 4> {
     /// Constructs a new, empty special header.
-    /// 
-    /// Due to this method requiring generics to be provided to the type to call,
-    /// it is recommended to instead call [`new_builder`].
-    pub const fn new() -> SpecialHeaderBuilder<0, 0, 0, 4> {
+    ///
+    /// This only exists on the empty shape, so that a builder type with values already in it
+    /// cannot be used to construct one. It is usually easier to call [`new_builder`].
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::header::{self, SpecialHeaderBuilder};
+    ///
+    /// assert_eq!(SpecialHeaderBuilder::new().build(), header::new_builder().build());
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use hipc::header;
+    ///
+    /// let _ = <header::header_ty!(true, 1, 0)>::new();
+    /// ```
+    pub const fn new() -> Self {
         SpecialHeaderBuilder {
             process_ids: [],
             copy_handles: [],
             move_handles: []
         }
     }
+}
+
+impl Default for SpecialHeaderBuilder<0, 0, 0, 4> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
 
+impl<const PIDS: usize, const CP: usize, const MV: usize, const TOTAL: usize> SpecialHeaderBuilder<PIDS, CP, MV, TOTAL> {
     /// Configures the special header to include the provided process ID
     /// 
     /// # Arguments
@@ -139,6 +263,134 @@ impl<const PIDS: usize, const CP: usize, const MV: usize, const TOTAL: usize> Sp
         }
     }
 
+    /// Combines the process IDs and handles of two special headers into one
+    ///
+    /// The handles of `self` come before those of `other` in each group.
+    ///
+    /// # Arguments
+    /// * `other` - The special header to merge into this one
+    ///
+    /// # Failures
+    /// * Both headers carry a process ID
+    /// * The combined copy or move handles exceed the maximum allowed (15)
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::header;
+    ///
+    /// let pid = header::new_builder().with_program_id(0x0100_0000_0000_1000);
+    /// let handles = header::new_builder().with_copy_handle(0xFFFF_8001).with_move_handle(0x1234);
+    ///
+    /// let merged = pid.merge(handles);
+    /// let expected = header::new_builder()
+    ///     .with_program_id(0x0100_0000_0000_1000)
+    ///     .with_copy_handle(0xFFFF_8001)
+    ///     .with_move_handle(0x1234);
+    ///
+    /// assert_eq!(merged.build(), expected.build());
+    /// ```
+    pub const fn merge<const PIDS2: usize, const CP2: usize, const MV2: usize, const TOTAL2: usize>(
+        self,
+        other: SpecialHeaderBuilder<PIDS2, CP2, MV2, TOTAL2>
+    ) -> SpecialHeaderBuilder<
+        { helpers::safe_add(PIDS, PIDS2, MAX_PIDS, "Too many process ids!") },
+        { helpers::safe_add(CP, CP2, MAX_COPY, "Too many copy handles!") },
+        { helpers::safe_add(MV, MV2, MAX_MOVE, "Too many move handles!") },
+        {
+            consumed_space(
+                helpers::safe_add(PIDS, PIDS2, MAX_PIDS, "Too many process ids!"),
+                helpers::safe_add(CP, CP2, MAX_COPY, "Too many copy handles!"),
+                helpers::safe_add(MV, MV2, MAX_MOVE, "Too many move handles!")
+            )
+        }
+    > {
+        SpecialHeaderBuilder {
+            process_ids: helpers::append_array(self.process_ids, other.process_ids, 0),
+            copy_handles: helpers::append_array(self.copy_handles, other.copy_handles, 0),
+            move_handles: helpers::append_array(self.move_handles, other.move_handles, 0)
+        }
+    }
+
+    /// The number of process IDs the special header carries (either 0 or 1)
+    pub const fn num_pids(self) -> usize {
+        PIDS
+    }
+
+    /// The number of handles the kernel will copy
+    pub const fn num_copy(self) -> usize {
+        CP
+    }
+
+    /// The number of handles the kernel will move
+    pub const fn num_move(self) -> usize {
+        MV
+    }
+
+    /// The number of bytes the special header consumes once serialized
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::header;
+    ///
+    /// const HEADER: header::header_ty!(true, 2, 1) = header::new_builder()
+    ///     .with_program_id(0)
+    ///     .with_copy_handle(1)
+    ///     .with_move_handle(2)
+    ///     .with_copy_handle(3);
+    ///
+    /// const _: () = assert!(HEADER.num_pids() == 1);
+    /// const _: () = assert!(HEADER.num_copy() == 2);
+    /// const _: () = assert!(HEADER.num_move() == 1);
+    /// const _: () = assert!(HEADER.byte_len() == 4 + 8 + 3 * 4);
+    ///
+    /// // Always a whole number of words, so the descriptors after it stay aligned
+    /// const _: () = assert!(HEADER.byte_len() % 4 == 0);
+    ///
+    /// assert_eq!(HEADER.build().len(), HEADER.byte_len());
+    /// ```
+    pub const fn byte_len(self) -> usize {
+        TOTAL
+    }
+
+    /// Checks that the special header does not transfer more handles than the caller has available
+    ///
+    /// # Arguments
+    /// * `available` - The number of handles the caller can transfer
+    ///
+    /// # Failures
+    /// * The combined number of copy and move handles exceeds `available`
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::header;
+    ///
+    /// const HEADER: header::header_ty!(false, 1, 1) = header::new_builder()
+    ///     .with_copy_handle(1)
+    ///     .with_move_handle(2)
+    ///     .assert_handle_budget(2);
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use hipc::header;
+    ///
+    /// const HEADER: header::header_ty!(false, 1, 1) = header::new_builder()
+    ///     .with_copy_handle(1)
+    ///     .with_move_handle(2)
+    ///     .assert_handle_budget(1);
+    /// ```
+    #[track_caller]
+    pub const fn assert_handle_budget(self, available: usize) -> Self {
+        const_assert!(
+            CP + MV <= available,
+            "Special header transfers more handles than are available!",
+            "copy handles" => CP,
+            "move handles" => MV,
+            "available" => available
+        );
+
+        self
+    }
+
     /// Compiles the special header into the smallest byte array that can contain it
     /// 
     /// While you can call this, it's not very useful as the only place this is used
@@ -211,5 +463,256 @@ impl<const PIDS: usize, const CP: usize, const MV: usize, const TOTAL: usize> Sp
 /// # }
 /// ```
 pub const fn new_builder() -> SpecialHeaderBuilder<0, 0, 0, 4> {
-    SpecialHeaderBuilder::<0, 0, 0, 4>::new()
-}
\ No newline at end of file
+    SpecialHeaderBuilder::new()
+}
+/// A special header parsed out of a received buffer
+///
+/// The process ID and handles are read lazily out of the borrowed bytes, which are
+/// guaranteed to be long enough for everything the header word declares.
+///
+/// # Example
+/// ```
+/// use hipc::header::{self, SpecialHeaderView};
+///
+/// let bytes = header::new_builder()
+///     .with_program_id(0x0100_0000_0000_1000)
+///     .with_copy_handle(0xFFFF_8001)
+///     .with_move_handle(0x1234)
+///     .with_copy_handle(0x5678)
+///     .build();
+///
+/// let view = SpecialHeaderView::from_bytes(&bytes).unwrap();
+/// assert_eq!(view.process_id(), Some(0x0100_0000_0000_1000));
+/// assert_eq!(view.num_copy_handles(), 2);
+/// assert_eq!(view.num_move_handles(), 1);
+/// assert!(view.copy_handles().eq([0xFFFF_8001, 0x5678]));
+/// assert!(view.move_handles().eq([0x1234]));
+/// assert_eq!(view.byte_len(), bytes.len());
+/// ```
+///
+/// # Ordering
+/// The ABI fixes the order of the values following the header word: the process ID, then the
+/// copy handles, then the move handles. The builder and the view must agree on it, no matter
+/// what order the values were added in.
+/// ```
+/// use hipc::{command, header::{self, SpecialHeaderView}};
+///
+/// const PID: u64 = 0x0102_0304_0506_0708;
+/// const COPY: [u32; 2] = [0xC0C0_0001, 0xC0C0_0002];
+/// const MOVE: [u32; 3] = [0x4040_0001, 0x4040_0002, 0x4040_0003];
+///
+/// let bytes = command::request_builder()
+///     .with_special_header(
+///         header::new_builder()
+///             .with_move_handle(MOVE[0])
+///             .with_copy_handle(COPY[0])
+///             .with_move_handle(MOVE[1])
+///             .with_program_id(PID)
+///             .with_copy_handle(COPY[1])
+///             .with_move_handle(MOVE[2])
+///     )
+///     .build();
+///
+/// // The special header follows the 8 byte command header
+/// let view = SpecialHeaderView::from_bytes(&bytes[8..]).unwrap();
+/// assert_eq!(view.process_id(), Some(PID));
+/// assert!(view.copy_handles().eq(COPY));
+/// assert!(view.move_handles().eq(MOVE));
+///
+/// // And each value sits at the offset the ABI puts it at
+/// let word = |offset: usize| u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap());
+/// assert_eq!(u64::from_le_bytes(bytes[0x0C..0x14].try_into().unwrap()), PID);
+/// assert_eq!([word(0x14), word(0x18)], COPY);
+/// assert_eq!([word(0x1C), word(0x20), word(0x24)], MOVE);
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct SpecialHeaderView<'a> {
+    header: crate::packed::SpecialHeader,
+    bytes: &'a [u8]
+}
+
+impl<'a> SpecialHeaderView<'a> {
+    /// Parses the special header at the start of `bytes`
+    ///
+    /// Anything past the end of the special header is ignored.
+    ///
+    /// # Arguments
+    /// * `bytes` - The buffer, starting at the special header word
+    ///
+    /// # Failures
+    /// * `bytes` is too short for the header word, or for the process ID and handles it declares
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{header::SpecialHeaderView, ParseError};
+    ///
+    /// // Claims 15 copy handles but only carries 2
+    /// let mut bytes = [0u8; 12];
+    /// bytes[0] = 15 << 1;
+    ///
+    /// assert_eq!(
+    ///     SpecialHeaderView::from_bytes(&bytes).unwrap_err(),
+    ///     ParseError::TruncatedSpecialHeader { needed: 4 + 15 * 4, available: 12 }
+    /// );
+    /// ```
+    pub const fn from_bytes(bytes: &'a [u8]) -> Result<Self, crate::ParseError> {
+        let word_size = core::mem::size_of::<crate::packed::SpecialHeader>();
+        if bytes.len() < word_size {
+            return Err(crate::ParseError::TruncatedSpecialHeader { needed: word_size, available: bytes.len() });
+        }
+
+        let header = crate::packed::SpecialHeader::from([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let needed = consumed_space(header.send_pid() as usize, header.num_copy_handles(), header.num_move_handles());
+
+        if bytes.len() < needed {
+            return Err(crate::ParseError::TruncatedSpecialHeader { needed, available: bytes.len() });
+        }
+
+        let (bytes, _) = bytes.split_at(needed);
+
+        Ok(Self { header, bytes })
+    }
+
+    /// The special header word
+    pub const fn header(&self) -> crate::packed::SpecialHeader {
+        self.header
+    }
+
+    /// The process ID carried by the header, if any
+    pub const fn process_id(&self) -> Option<u64> {
+        if !self.header.send_pid() {
+            return None;
+        }
+
+        let low = crate::util::read_word(self.bytes, 4) as u64;
+        let high = crate::util::read_word(self.bytes, 8) as u64;
+        Some(low | (high << 32))
+    }
+
+    /// The number of handles the kernel copied
+    pub const fn num_copy_handles(&self) -> usize {
+        self.header.num_copy_handles()
+    }
+
+    /// The number of handles the kernel moved
+    pub const fn num_move_handles(&self) -> usize {
+        self.header.num_move_handles()
+    }
+
+    /// The copy handle at `index`, if there is one
+    pub const fn copy_handle(&self, index: usize) -> Option<u32> {
+        if index >= self.num_copy_handles() {
+            return None;
+        }
+
+        Some(crate::util::read_word(self.bytes, self.copy_handles_offset() + index * core::mem::size_of::<u32>()))
+    }
+
+    /// The move handle at `index`, if there is one
+    pub const fn move_handle(&self, index: usize) -> Option<u32> {
+        if index >= self.num_move_handles() {
+            return None;
+        }
+
+        Some(crate::util::read_word(self.bytes, self.move_handles_offset() + index * core::mem::size_of::<u32>()))
+    }
+
+    /// Iterates over the copy handles, in the order they were sent
+    pub fn copy_handles(&self) -> impl Iterator<Item = u32> + 'a {
+        let start = self.copy_handles_offset();
+        Self::words(&self.bytes[start..self.move_handles_offset()])
+    }
+
+    /// Iterates over the move handles, in the order they were sent
+    pub fn move_handles(&self) -> impl Iterator<Item = u32> + 'a {
+        Self::words(&self.bytes[self.move_handles_offset()..])
+    }
+
+    /// The number of bytes the special header occupies
+    pub const fn byte_len(&self) -> usize {
+        self.bytes.len()
+    }
+
+    /// The raw bytes of the special header
+    pub const fn as_bytes(&self) -> &'a [u8] {
+        self.bytes
+    }
+
+    const fn copy_handles_offset(&self) -> usize {
+        core::mem::size_of::<crate::packed::SpecialHeader>() + self.header.send_pid() as usize * PROCESS_ID_SIZE
+    }
+
+    const fn move_handles_offset(&self) -> usize {
+        self.copy_handles_offset() + self.num_copy_handles() * core::mem::size_of::<u32>()
+    }
+
+    fn words(bytes: &'a [u8]) -> impl Iterator<Item = u32> + 'a {
+        bytes.chunks_exact(4).map(|word| u32::from_le_bytes([word[0], word[1], word[2], word[3]]))
+    }
+}
+
+/// The process ID, copy handles, and move handles of a special header, in that order
+pub type SpecialHeaderParts<'a> = (Option<u64>, &'a [u32], &'a [u32]);
+
+/// Splits a special header read as words, such as a view of the TLS, into its process ID,
+/// copy handles, and move handles
+///
+/// Unlike [`SpecialHeaderView`], the handles are borrowed straight out of `words`. Anything
+/// past the end of the special header is ignored.
+///
+/// # Arguments
+/// * `words` - The buffer, starting at the special header word
+///
+/// # Failures
+/// * `words` is too short for the header word, or for the process ID and handles it declares
+///
+/// # Example
+/// ```
+/// use hipc::{header, ParseError};
+///
+/// let bytes = header::new_builder()
+///     .with_program_id(0x0100_0000_0000_1000)
+///     .with_copy_handle(0xFFFF_8001)
+///     .with_move_handle(0x1234)
+///     .with_move_handle(0x5678)
+///     .build();
+/// let words: Vec<u32> = bytes.chunks(4).map(|word| u32::from_le_bytes(word.try_into().unwrap())).collect();
+///
+/// let (process_id, copy, move_) = header::parse_special_header(&words).unwrap();
+/// assert_eq!(process_id, Some(0x0100_0000_0000_1000));
+/// assert_eq!(copy, &[0xFFFF_8001]);
+/// assert_eq!(move_, &[0x1234, 0x5678]);
+///
+/// // Claims 15 copy handles but only carries 2
+/// assert_eq!(
+///     header::parse_special_header(&[15 << 1, 0, 0]),
+///     Err(ParseError::TruncatedSpecialHeader { needed: 4 + 15 * 4, available: 12 })
+/// );
+/// ```
+pub const fn parse_special_header(words: &[u32]) -> Result<SpecialHeaderParts<'_>, crate::ParseError> {
+    let word_size = core::mem::size_of::<u32>();
+    if words.is_empty() {
+        return Err(crate::ParseError::TruncatedSpecialHeader { needed: word_size, available: 0 });
+    }
+
+    let header = crate::packed::SpecialHeader::from_bytes(words[0].to_le_bytes());
+    let needed = consumed_space(header.send_pid() as usize, header.num_copy_handles(), header.num_move_handles());
+
+    let available = core::mem::size_of_val(words);
+    if available < needed {
+        return Err(crate::ParseError::TruncatedSpecialHeader { needed, available });
+    }
+
+    let (_, rest) = words.split_at(1);
+    let (process_id, rest) = if header.send_pid() {
+        let (process_id, rest) = rest.split_at(PROCESS_ID_SIZE / word_size);
+        (Some(process_id[0] as u64 | ((process_id[1] as u64) << 32)), rest)
+    } else {
+        (None, rest)
+    };
+
+    let (copy_handles, rest) = rest.split_at(header.num_copy_handles());
+    let (move_handles, _) = rest.split_at(header.num_move_handles());
+
+    Ok((process_id, copy_handles, move_handles))
+}
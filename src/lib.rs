@@ -6,12 +6,29 @@
 #![feature(generic_const_exprs)]
 #![feature(const_convert)]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(feature = "alloc")]
+pub mod boxed;
+pub mod cmif;
 pub mod command;
+pub mod control;
+pub mod domain;
 pub mod header;
 pub mod packed;
+pub mod raw_data;
+pub mod replay;
+pub mod result;
+pub mod service;
+pub mod util;
 
 /// Command type for HIPC commands
 #[repr(u16)]
+#[derive(Copy, Clone, Debug)]
 pub enum CommandType {
     /// An invalid command type, also used by servers when issuing a response to the
     /// client
@@ -42,6 +59,134 @@ pub enum CommandType {
     ControlWithContext = 0x7
 }
 
+impl CommandType {
+    /// Every command type, in order of their values
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::CommandType;
+    ///
+    /// assert_eq!(CommandType::ALL.len(), 8);
+    /// for (value, ty) in CommandType::ALL.into_iter().enumerate() {
+    ///     assert_eq!(ty as u16, value as u16);
+    /// }
+    /// ```
+    pub const ALL: [CommandType; 8] = [
+        CommandType::Invalid,
+        CommandType::LegacyRequest,
+        CommandType::Close,
+        CommandType::LegacyControl,
+        CommandType::Request,
+        CommandType::Control,
+        CommandType::RequestWithContext,
+        CommandType::ControlWithContext
+    ];
+}
+
+/// Converts the type field of a received header back into a command type
+///
+/// # Example
+/// ```
+/// use hipc::{CommandType, ParseError};
+///
+/// for ty in CommandType::ALL {
+///     assert_eq!(CommandType::try_from(ty as u16).map(|ty| ty as u16), Ok(ty as u16));
+/// }
+///
+/// assert!(matches!(CommandType::try_from(4), Ok(CommandType::Request)));
+/// assert!(matches!(CommandType::try_from(2), Ok(CommandType::Close)));
+/// assert_eq!(CommandType::try_from(8).map(|ty| ty as u16), Err(ParseError::InvalidCommandType(8)));
+/// assert_eq!(CommandType::try_from(0x10).map(|ty| ty as u16), Err(ParseError::InvalidCommandType(0x10)));
+/// assert_eq!(CommandType::try_from(u16::MAX).map(|ty| ty as u16), Err(ParseError::InvalidCommandType(u16::MAX)));
+/// ```
+impl const TryFrom<u16> for CommandType {
+    type Error = ParseError;
+
+    fn try_from(value: u16) -> Result<Self, Self::Error> {
+        if (value as usize) < CommandType::ALL.len() {
+            Ok(CommandType::ALL[value as usize])
+        } else {
+            Err(ParseError::InvalidCommandType(value))
+        }
+    }
+}
+
+/// Formats the command type as a short, human readable description
+///
+/// # Example
+/// ```
+/// use hipc::CommandType;
+///
+/// assert_eq!(CommandType::Request.to_string(), "Request command");
+/// assert_eq!(CommandType::Close.to_string(), "Close session command");
+/// assert_eq!(CommandType::ControlWithContext.to_string(), "Control command with context");
+/// ```
+impl core::fmt::Display for CommandType {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let description = match self {
+            CommandType::Invalid => "Invalid command",
+            CommandType::LegacyRequest => "Legacy request command",
+            CommandType::Close => "Close session command",
+            CommandType::LegacyControl => "Legacy control command",
+            CommandType::Request => "Request command",
+            CommandType::Control => "Control command",
+            CommandType::RequestWithContext => "Request command with context",
+            CommandType::ControlWithContext => "Control command with context"
+        };
+
+        f.write_str(description)
+    }
+}
+
+/// Errors produced when decoding a received command
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The buffer is too short for the sections the header declares
+    Truncated {
+        /// The number of bytes the command needs
+        needed: usize,
+
+        /// The number of bytes actually available
+        available: usize
+    },
+
+    /// The header's reserved bits are not zero
+    ReservedBitsSet(u32),
+
+    /// The header declares a command larger than the TLS can hold
+    TooLarge(usize),
+
+    /// The header's type field is not one of the [`CommandType`] values
+    InvalidCommandType(u16),
+
+    /// The special header declares more process ID and handle words than the buffer contains
+    TruncatedSpecialHeader {
+        /// The number of bytes the special header declares, including its own word
+        needed: usize,
+
+        /// The number of bytes actually available
+        available: usize
+    },
+
+    /// A domain request or response declares more object IDs than the raw data contains
+    TruncatedObjectIds {
+        /// The number of bytes the CMIF data and object IDs need
+        needed: usize,
+
+        /// The number of bytes actually available
+        available: usize
+    },
+
+    /// The header's receive list offset points before the end of the raw data
+    MisplacedReceiveList {
+        /// The byte offset the receive list offset points to
+        offset: usize,
+
+        /// The byte offset the raw data ends at
+        raw_data_end: usize
+    }
+}
+
 /// Helper trait for converting into an array of 32-bit words
 #[const_trait]
 pub trait IntoWords<const N: usize>: ~const Into<[u32; N]> {}
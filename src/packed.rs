@@ -2,6 +2,8 @@
 
 use core::ops::*;
 
+use crate::util::const_assert;
+
 #[const_trait]
 trait ConstUnsigned:
     ~const BitAnd<Self, Output = Self> +
@@ -46,8 +48,48 @@ const fn set<T: ~const ConstUnsigned>(src: T, dst: T, src_lsb: usize, dst_lsb: u
     new_value | (value << dst_lsb)
 }
 
+/// Formats a value as hexadecimal inside of `Debug` output
+struct Hex(u64);
+
+impl core::fmt::Debug for Hex {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{:#x}", self.0)
+    }
+}
+
+/// Formats a buffer mode by name, or by value if it is not a defined mode
+struct Mode(u8);
+
+impl core::fmt::Debug for Mode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match BufferMode::try_from_u8(self.0) {
+            Some(mode) => mode.fmt(f),
+            None => write!(f, "Unknown({})", self.0)
+        }
+    }
+}
+
+/// Errors produced when validating a descriptor
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum DescriptorError {
+    /// The descriptor has a nonzero size but no address
+    NullAddress,
+
+    /// The descriptor has an address but a size of zero
+    ZeroSize,
+
+    /// The descriptor's address does not have the required alignment
+    Misaligned,
+
+    /// The descriptor's size does not fit in the bits its format reserves for it
+    SizeTooLarge
+}
+
+/// A send static (InPointer) descriptor
+///
+/// Ordering and hashing operate on the raw packed words, not on the decoded fields.
 #[repr(C)]
-#[derive(Copy, Clone, PartialEq, Eq, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct StaticDescriptor([u32; 2]);
 
 impl StaticDescriptor {
@@ -73,6 +115,84 @@ impl StaticDescriptor {
         let second = set(address, 0, 0, 0, 32) as u32;
         Self([first, second])
     }
+
+    /// Constructs a descriptor from its words verbatim, as they appear in a received command
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::StaticDescriptor;
+    ///
+    /// let bytes: [u8; 8] = StaticDescriptor::new(3, 0x80, 0x12_3456_7000).into();
+    /// let desc = StaticDescriptor::new_raw([
+    ///     u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+    ///     u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]])
+    /// ]);
+    /// assert_eq!(desc.into_parts(), (3, 0x80, 0x12_3456_7000));
+    /// ```
+    pub const fn new_raw(words: [u32; 2]) -> Self {
+        Self(words)
+    }
+
+    /// Reads a descriptor back out of its serialized bytes
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::StaticDescriptor;
+    ///
+    /// let bytes: [u8; 8] = StaticDescriptor::new(3, 0x80, 0x12_3456_7000).into();
+    /// let desc = StaticDescriptor::from_bytes(bytes);
+    ///
+    /// assert_eq!(desc.index(), 3);
+    /// assert_eq!(desc.size(), 0x80);
+    /// assert_eq!(desc.address(), 0x12_3456_7000);
+    /// ```
+    pub const fn from_bytes(bytes: [u8; 8]) -> Self {
+        Self([
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]])
+        ])
+    }
+
+    /// Decodes every field of the descriptor at once, in the same order as [`StaticDescriptor::new`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::StaticDescriptor;
+    ///
+    /// let desc = StaticDescriptor::new(3, 0x80, 0x12_3456_7000);
+    /// assert_eq!(desc.into_parts(), (3, 0x80, 0x12_3456_7000));
+    /// ```
+    pub const fn into_parts(self) -> (usize, usize, u64) {
+        (self.index(), self.size(), self.address())
+    }
+
+    /// Re-expresses the static as a send buffer over the same memory
+    ///
+    /// The address and size carry over unchanged. The static's index has no equivalent on a
+    /// buffer descriptor and is dropped, so converting back requires choosing an index again.
+    ///
+    /// # Arguments
+    /// * `mode` - The mapping mode for the buffer descriptor
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::StaticDescriptor;
+    ///
+    /// let desc = StaticDescriptor::new(3, 0x80, 0x12_3456_7000);
+    /// let buffer = desc.to_send_buffer(1);
+    /// assert_eq!(buffer.into_parts(), (0x12_3456_7000, 0x80, 1));
+    ///
+    /// assert!(buffer.to_send_static(3) == desc);
+    /// ```
+    pub const fn to_send_buffer(self, mode: u8) -> BufferDescriptor {
+        BufferDescriptor::new(self.address(), self.size(), mode)
+    }
+}
+
+impl const From<StaticDescriptor> for (usize, usize, u64) {
+    fn from(value: StaticDescriptor) -> Self {
+        value.into_parts()
+    }
 }
 
 impl const From<StaticDescriptor> for [u8; 8] {
@@ -93,8 +213,80 @@ impl const From<StaticDescriptor> for [u8; 8] {
     }
 }
 
+/// Prints the decoded fields rather than the packed words
+///
+/// # Example
+/// ```
+/// use hipc::packed::StaticDescriptor;
+///
+/// let desc = StaticDescriptor::new(2, 0x100, 0x1234_5000);
+/// assert_eq!(format!("{desc:?}"), "StaticDescriptor { index: 2, address: 0x12345000, size: 256 }");
+/// ```
+impl core::fmt::Debug for StaticDescriptor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("StaticDescriptor")
+            .field("index", &self.index())
+            .field("address", &Hex(self.address()))
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+/// The number of address bits a buffer descriptor can reference
+const BUFFER_ADDRESS_BITS: u64 = 39;
+
+/// The mapping mode of a buffer descriptor
+///
+/// The mode field is 2 bits wide, the value 2 has no meaning.
+#[repr(u8)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BufferMode {
+    /// The default mode, used by nearly every buffer
+    Normal = 0,
+
+    /// The buffer may be mapped as non-secure memory
+    NonSecure = 1,
+
+    /// The buffer may be mapped as non-device memory
+    NonDevice = 3
+}
+
+impl BufferMode {
+    /// The value of the mode as stored in a descriptor
+    pub const fn as_u8(self) -> u8 {
+        self as u8
+    }
+
+    /// Converts a raw mode value into a mode
+    ///
+    /// # Returns
+    /// * `None` if `value` is not one of the defined modes
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::BufferMode;
+    ///
+    /// assert_eq!(BufferMode::try_from_u8(0), Some(BufferMode::Normal));
+    /// assert_eq!(BufferMode::try_from_u8(1), Some(BufferMode::NonSecure));
+    /// assert_eq!(BufferMode::try_from_u8(2), None);
+    /// assert_eq!(BufferMode::try_from_u8(3), Some(BufferMode::NonDevice));
+    /// assert_eq!(BufferMode::try_from_u8(4), None);
+    /// ```
+    pub const fn try_from_u8(value: u8) -> Option<Self> {
+        match value {
+            0 => Some(BufferMode::Normal),
+            1 => Some(BufferMode::NonSecure),
+            3 => Some(BufferMode::NonDevice),
+            _ => None
+        }
+    }
+}
+
+/// A send/receive/exchange buffer (MapAlias) descriptor
+///
+/// Ordering and hashing operate on the raw packed words, not on the decoded fields.
 #[repr(C)]
-#[derive(Copy, Clone, PartialEq, Eq, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct BufferDescriptor([u32; 3]);
 
 impl BufferDescriptor {
@@ -127,6 +319,275 @@ impl BufferDescriptor {
 
         Self([size_low, address_low, inner])
     }
+
+    /// Constructs a descriptor with a mode that is known to be valid
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::{BufferDescriptor, BufferMode};
+    ///
+    /// let desc = BufferDescriptor::new_typed(0x12_3456_7000, 0x400, BufferMode::NonDevice);
+    /// assert_eq!(desc.mode(), 3);
+    /// assert_eq!(desc.mode_typed(), Some(BufferMode::NonDevice));
+    /// assert!(desc == BufferDescriptor::new(0x12_3456_7000, 0x400, 3));
+    ///
+    /// // A raw mode of 2 has no typed equivalent
+    /// assert_eq!(BufferDescriptor::new(0x12_3456_7000, 0x400, 2).mode_typed(), None);
+    /// ```
+    pub const fn new_typed(address: u64, size: usize, mode: BufferMode) -> Self {
+        Self::new(address, size, mode.as_u8())
+    }
+
+    /// The mode of the descriptor, or `None` if it holds the undefined value 2
+    pub const fn mode_typed(self) -> Option<BufferMode> {
+        BufferMode::try_from_u8(self.mode())
+    }
+
+    /// Constructs a descriptor with mode 0 (Normal), which nearly every buffer uses
+    ///
+    /// Buffers that need mode 1 (NonSecure) or mode 3 (NonDevice) must go through
+    /// [`BufferDescriptor::new_typed`] or [`BufferDescriptor::new`].
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::BufferDescriptor;
+    ///
+    /// let desc = BufferDescriptor::normal(0x12_3456_7000, 0x400);
+    /// assert_eq!(desc.mode(), 0);
+    /// assert!(desc == BufferDescriptor::new(0x12_3456_7000, 0x400, 0));
+    /// ```
+    pub const fn normal(address: u64, size: usize) -> Self {
+        Self::new(address, size, 0)
+    }
+
+    /// Replaces the address of the descriptor, keeping its size and mode
+    ///
+    /// # Arguments
+    /// * `address` - The new address of the buffer
+    ///
+    /// # Panicking
+    /// Panics if `address` does not fit in the 39 bits of address space a descriptor can reference
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::BufferDescriptor;
+    ///
+    /// let desc = BufferDescriptor::new(0x1000, 0x4_2000_0000, 2).with_address(0x7F_FFFF_F000);
+    /// assert_eq!(desc.into_parts(), (0x7F_FFFF_F000, 0x4_2000_0000, 2));
+    /// ```
+    ///
+    /// ```should_panic
+    /// use hipc::packed::BufferDescriptor;
+    ///
+    /// let _ = BufferDescriptor::new(0x1000, 0x100, 0).with_address(1 << 39);
+    /// ```
+    #[track_caller]
+    pub const fn with_address(self, address: u64) -> BufferDescriptor {
+        const_assert!(address >> BUFFER_ADDRESS_BITS == 0, "Buffer address does not fit in 39 bits", "address" => address);
+
+        Self::new(address, self.size(), self.mode())
+    }
+
+    /// Replaces the size of the descriptor, keeping its address and mode
+    ///
+    /// # Arguments
+    /// * `size` - The new size of the buffer
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::BufferDescriptor;
+    ///
+    /// let desc = BufferDescriptor::new(0x12_3456_7000, 0x100, 1).with_size(0x8_0000_1000);
+    /// assert_eq!(desc.into_parts(), (0x12_3456_7000, 0x8_0000_1000, 1));
+    /// ```
+    pub const fn with_size(self, size: usize) -> BufferDescriptor {
+        Self::new(self.address(), size, self.mode())
+    }
+
+    /// Describes the memory of `slice`, using its pointer as the address and its length as the size
+    ///
+    /// The descriptor only records the address, so nothing ties it to the slice's lifetime.
+    /// The slice must stay alive (and must not move) until the IPC call using the descriptor
+    /// has completed, otherwise the server reads freed or reused memory.
+    ///
+    /// As with [`BufferDescriptor::new`], address bits past the 39 a descriptor can hold are
+    /// dropped. On the console user memory always fits.
+    ///
+    /// # Arguments
+    /// * `slice` - The memory the server will read from
+    /// * `mode` - The buffer's mode
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::BufferDescriptor;
+    ///
+    /// let buffer = [0u8; 0x40];
+    /// let desc = BufferDescriptor::from_slice(&buffer, 1);
+    ///
+    /// assert_eq!(desc.size(), 0x40);
+    /// assert_eq!(desc.mode(), 1);
+    /// assert!(desc == BufferDescriptor::new(buffer.as_ptr() as u64, buffer.len(), 1));
+    /// ```
+    #[cfg(any(feature = "std", target_os = "horizon"))]
+    pub fn from_slice(slice: &[u8], mode: u8) -> BufferDescriptor {
+        Self::new(slice.as_ptr() as u64, slice.len(), mode)
+    }
+
+    /// Describes the memory of `slice`, the same as [`BufferDescriptor::from_slice`], for
+    /// buffers the server writes to
+    ///
+    /// The same lifetime caveat applies: `slice` must outlive the IPC call, and must not be
+    /// accessed through any other reference while the server may still write to it.
+    ///
+    /// # Arguments
+    /// * `slice` - The memory the server will write to
+    /// * `mode` - The buffer's mode
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, packed::BufferDescriptor};
+    ///
+    /// let mut out = [0u8; 0x100];
+    /// let desc = BufferDescriptor::from_mut_slice(&mut out, 0);
+    /// assert_eq!(desc.size(), 0x100);
+    ///
+    /// let bytes = command::request_builder().with_recv_buffer(desc).build();
+    /// assert_eq!(&bytes[8..20], &<[u8; 12]>::from(desc));
+    /// ```
+    #[cfg(any(feature = "std", target_os = "horizon"))]
+    pub fn from_mut_slice(slice: &mut [u8], mode: u8) -> BufferDescriptor {
+        Self::new(slice.as_mut_ptr() as u64, slice.len(), mode)
+    }
+
+    /// Re-expresses the buffer as a send static over the same memory
+    ///
+    /// The address and size carry over unchanged. The buffer's mode has no equivalent on a
+    /// static descriptor and is dropped, so converting back requires choosing a mode again.
+    ///
+    /// # Arguments
+    /// * `index` - The index of the static, which pairs it with the receiver's receive list entry
+    ///
+    /// # Panicking
+    /// * The buffer is larger than a static can describe (`0xFFFF` bytes)
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::BufferDescriptor;
+    ///
+    /// let desc = BufferDescriptor::new(0x12_3456_7000, 0x400, 2);
+    /// let static_desc = desc.to_send_static(5);
+    /// assert_eq!(static_desc.into_parts(), (5, 0x400, 0x12_3456_7000));
+    ///
+    /// assert!(static_desc.to_send_buffer(2) == desc);
+    /// ```
+    ///
+    /// ```should_panic
+    /// use hipc::packed::BufferDescriptor;
+    ///
+    /// let _ = BufferDescriptor::new(0x1000, 0x1_0000, 0).to_send_static(0);
+    /// ```
+    #[track_caller]
+    pub const fn to_send_static(self, index: usize) -> StaticDescriptor {
+        const_assert!(self.size() <= 0xFFFF, "Buffer is too large to be described by a send static", "size" => self.size());
+
+        StaticDescriptor::new(index, self.size(), self.address())
+    }
+
+    /// Decodes every field of the descriptor at once, in the same order as [`BufferDescriptor::new`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::BufferDescriptor;
+    ///
+    /// let desc = BufferDescriptor::new(0x12_3456_7000, 0x4000, 1);
+    /// assert_eq!(desc.into_parts(), (0x12_3456_7000, 0x4000, 1));
+    ///
+    /// let (address, size, mode): (u64, usize, u8) = desc.into();
+    /// assert_eq!((address, size, mode), (desc.address(), desc.size(), desc.mode()));
+    /// ```
+    pub const fn into_parts(self) -> (u64, usize, u8) {
+        (self.address(), self.size(), self.mode())
+    }
+
+    /// Reads a descriptor back out of its serialized bytes
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::BufferDescriptor;
+    ///
+    /// let bytes: [u8; 12] = BufferDescriptor::new(0x7F_1234_5000, 0xA_0000_0100, 3).into();
+    /// let desc = BufferDescriptor::from_bytes(bytes);
+    ///
+    /// assert_eq!(desc.address(), 0x7F_1234_5000);
+    /// assert_eq!(desc.size(), 0xA_0000_0100);
+    /// assert_eq!(desc.mode(), 3);
+    /// ```
+    pub const fn from_bytes(bytes: [u8; 12]) -> Self {
+        Self([
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]),
+            u32::from_le_bytes([bytes[8], bytes[9], bytes[10], bytes[11]])
+        ])
+    }
+
+    /// Checks the descriptor for an inconsistent address/size pair
+    ///
+    /// A descriptor with both a zero address and a zero size is considered an empty buffer
+    /// and is accepted.
+    ///
+    /// # Failures
+    /// * The descriptor has a nonzero size but a zero address
+    /// * The descriptor has a nonzero address but a zero size
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::{BufferDescriptor, DescriptorError};
+    ///
+    /// assert_eq!(BufferDescriptor::new(0x1000, 0x100, 0).validate(), Ok(()));
+    /// assert_eq!(BufferDescriptor::new(0, 0, 0).validate(), Ok(()));
+    /// assert_eq!(BufferDescriptor::new(0, 0x100, 0).validate(), Err(DescriptorError::NullAddress));
+    /// assert_eq!(BufferDescriptor::new(0x1000, 0, 0).validate(), Err(DescriptorError::ZeroSize));
+    /// ```
+    pub const fn validate(self) -> Result<(), DescriptorError> {
+        match (self.address(), self.size()) {
+            (0, 0) => Ok(()),
+            (0, _) => Err(DescriptorError::NullAddress),
+            (_, 0) => Err(DescriptorError::ZeroSize),
+            _ => Ok(())
+        }
+    }
+
+    /// Checks the descriptor the same way as [`BufferDescriptor::validate`], additionally
+    /// requiring the address to be a multiple of `alignment`
+    ///
+    /// # Arguments
+    /// * `alignment` - The required alignment of the address, in bytes (must be nonzero)
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::{BufferDescriptor, DescriptorError};
+    ///
+    /// assert_eq!(BufferDescriptor::new(0x2000, 0x100, 0).validate_aligned(0x1000), Ok(()));
+    /// assert_eq!(BufferDescriptor::new(0x2010, 0x100, 0).validate_aligned(0x1000), Err(DescriptorError::Misaligned));
+    /// assert_eq!(BufferDescriptor::new(0, 0x100, 0).validate_aligned(0x1000), Err(DescriptorError::NullAddress));
+    /// ```
+    pub const fn validate_aligned(self, alignment: u64) -> Result<(), DescriptorError> {
+        if let Err(err) = self.validate() {
+            return Err(err);
+        }
+
+        if self.address() % alignment != 0 {
+            return Err(DescriptorError::Misaligned);
+        }
+
+        Ok(())
+    }
+}
+
+impl const From<BufferDescriptor> for (u64, usize, u8) {
+    fn from(value: BufferDescriptor) -> Self {
+        value.into_parts()
+    }
 }
 
 impl const From<BufferDescriptor> for [u8; 12] {
@@ -149,8 +610,39 @@ impl const From<BufferDescriptor> for [u8; 12] {
     }
 }
 
+/// Prints the decoded fields rather than the packed words
+///
+/// # Example
+/// ```
+/// use hipc::packed::{BufferDescriptor, BufferMode};
+///
+/// let desc = BufferDescriptor::new_typed(0x8000_0000, 0x40, BufferMode::NonSecure);
+/// assert_eq!(format!("{desc:?}"), "BufferDescriptor { address: 0x80000000, size: 64, mode: NonSecure }");
+///
+/// // Modes without a name are printed by value
+/// let desc = BufferDescriptor::new(0x8000_0000, 0x40, 2);
+/// assert_eq!(format!("{desc:?}"), "BufferDescriptor { address: 0x80000000, size: 64, mode: Unknown(2) }");
+/// ```
+impl core::fmt::Debug for BufferDescriptor {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("BufferDescriptor")
+            .field("address", &Hex(self.address()))
+            .field("size", &self.size())
+            .field("mode", &Mode(self.mode()))
+            .finish()
+    }
+}
+
+/// An entry in the command's receive list
+///
+/// The receive list plays two roles depending on the header's receive static mode. With
+/// receive statics (modes 3 and up) each entry is the destination for the out-pointer with
+/// the same index. In pointer buffer mode (mode 2) the list holds a single
+/// [`PointerBufferEntry`] instead. Both use the same encoding.
+///
+/// Ordering and hashing operate on the raw packed words, not on the decoded fields.
 #[repr(C)]
-#[derive(Copy, Clone, PartialEq, Eq, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
 pub struct ReceiveListEntry([u32; 2]);
 
 impl ReceiveListEntry {
@@ -163,6 +655,29 @@ impl ReceiveListEntry {
         set(self.0[1] as u64, address, 0, 32, 16)
     }
 
+    /// The largest size a receive list entry can describe
+    ///
+    /// The C descriptor format only has 16 bits for the size, with the remaining bits of the
+    /// second word holding the top of the address. There is no wider encoding, larger
+    /// out-pointers have to be split or sent as receive buffers instead.
+    pub const MAX_SIZE: usize = 0xFFFF;
+
+    /// Creates a receive list entry
+    ///
+    /// Only the low 16 bits of `size` are kept, see [`ReceiveListEntry::try_new`] for a version
+    /// that rejects sizes the format cannot carry.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::ReceiveListEntry;
+    ///
+    /// let bytes: [u8; 8] = ReceiveListEntry::new(0x12_3456_7000, 0x200).into();
+    /// assert_eq!(bytes, [
+    ///     0x00, 0x70, 0x56, 0x34, // Address bits 0..32
+    ///     0x12, 0x00,             // Address bits 32..48
+    ///     0x00, 0x02              // Size
+    /// ]);
+    /// ```
     pub const fn new(address: u64, size: usize) -> Self {
         let first = extract(address, 0, 32) as u32;
         let second = set(address, 0, 32, 0, 16) as u32;
@@ -170,6 +685,130 @@ impl ReceiveListEntry {
 
         Self([first, second])
     }
+
+    /// Creates a receive list entry, failing if the size does not fit in the format
+    ///
+    /// # Failures
+    /// * `size` is greater than [`ReceiveListEntry::MAX_SIZE`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::{DescriptorError, ReceiveListEntry};
+    ///
+    /// // The largest size that can be described
+    /// let entry = ReceiveListEntry::try_new(0xFFFF_1000_2000, 0xFFFF).unwrap();
+    /// let bytes: [u8; 8] = entry.into();
+    /// assert_eq!(bytes, [0x00, 0x20, 0x00, 0x10, 0xFF, 0xFF, 0xFF, 0xFF]);
+    /// assert_eq!(entry.into_parts(), (0xFFFF_1000_2000, 0xFFFF));
+    ///
+    /// // One past it would be truncated to zero by `new`
+    /// assert!(ReceiveListEntry::try_new(0x1000, 0x1_0000) == Err(DescriptorError::SizeTooLarge));
+    /// assert_eq!(ReceiveListEntry::new(0x1000, 0x1_0000).size(), 0);
+    /// ```
+    pub const fn try_new(address: u64, size: usize) -> Result<Self, DescriptorError> {
+        if size > Self::MAX_SIZE {
+            return Err(DescriptorError::SizeTooLarge);
+        }
+
+        Ok(Self::new(address, size))
+    }
+
+    /// Decodes every field of the entry at once, in the same order as [`ReceiveListEntry::new`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::ReceiveListEntry;
+    ///
+    /// let entry = ReceiveListEntry::new(0x12_3456_7000, 0x200);
+    /// assert_eq!(entry.into_parts(), (0x12_3456_7000, 0x200));
+    /// ```
+    pub const fn into_parts(self) -> (u64, usize) {
+        (self.address(), self.size())
+    }
+
+    /// Reads an entry back out of its serialized bytes
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::ReceiveListEntry;
+    ///
+    /// let bytes: [u8; 8] = ReceiveListEntry::new(0x12_3456_7000, 0x200).into();
+    /// let entry = ReceiveListEntry::from_bytes(bytes);
+    ///
+    /// assert_eq!(entry.address(), 0x12_3456_7000);
+    /// assert_eq!(entry.size(), 0x200);
+    /// ```
+    pub const fn from_bytes(bytes: [u8; 8]) -> Self {
+        Self([
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]])
+        ])
+    }
+}
+
+/// The single receive list entry of a command in pointer buffer mode
+///
+/// Rather than pairing with one out-pointer, this describes the buffer the server copies all
+/// of the command's send statics into, so its size is the pointer buffer's whole capacity. It
+/// is encoded exactly like a [`ReceiveListEntry`] and converts to one for
+/// [`with_pointer_buffer`](crate::command::HipcCommandBuilder::with_pointer_buffer).
+///
+/// # Example
+/// ```
+/// use hipc::{command, packed::{PointerBufferEntry, ReceiveListEntry}};
+///
+/// let entry = PointerBufferEntry::new(0x1234_5000, 0x500);
+/// assert_eq!(entry.address(), 0x1234_5000);
+/// assert_eq!(entry.capacity(), 0x500);
+///
+/// let bytes = command::request_builder().with_pointer_buffer(entry.into()).build();
+/// assert_eq!(&bytes[8..], &<[u8; 8]>::from(ReceiveListEntry::new(0x1234_5000, 0x500)));
+///
+/// // A receive static decodes to the same fields, the mode decides how they are used
+/// let parsed = PointerBufferEntry::from_entry(ReceiveListEntry::new(0x1234_5000, 0x500));
+/// assert!(parsed == entry);
+/// ```
+#[repr(transparent)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub struct PointerBufferEntry(ReceiveListEntry);
+
+impl PointerBufferEntry {
+    /// The address of the pointer buffer
+    pub const fn address(self) -> u64 {
+        self.0.address()
+    }
+
+    /// The number of bytes of send static data the pointer buffer can hold
+    pub const fn capacity(self) -> usize {
+        self.0.size()
+    }
+
+    /// Creates a pointer buffer entry
+    ///
+    /// # Arguments
+    /// * `address` - The address of the pointer buffer
+    /// * `capacity` - The size of the pointer buffer, usually the one reported by the server's
+    ///   `QueryPointerBufferSize` control command
+    pub const fn new(address: u64, capacity: usize) -> Self {
+        Self(ReceiveListEntry::new(address, capacity))
+    }
+
+    /// Reinterprets a receive list entry, such as one read out of a command in pointer buffer mode
+    pub const fn from_entry(entry: ReceiveListEntry) -> Self {
+        Self(entry)
+    }
+}
+
+impl const From<PointerBufferEntry> for ReceiveListEntry {
+    fn from(value: PointerBufferEntry) -> Self {
+        value.0
+    }
+}
+
+impl const From<ReceiveListEntry> for (u64, usize) {
+    fn from(value: ReceiveListEntry) -> Self {
+        value.into_parts()
+    }
 }
 
 impl const From<ReceiveListEntry> for [u8; 8] {
@@ -190,6 +829,24 @@ impl const From<ReceiveListEntry> for [u8; 8] {
     }
 }
 
+impl core::fmt::Debug for ReceiveListEntry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("ReceiveListEntry")
+            .field("address", &Hex(self.address()))
+            .field("size", &self.size())
+            .finish()
+    }
+}
+
+impl core::fmt::Debug for PointerBufferEntry {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("PointerBufferEntry")
+            .field("address", &Hex(self.address()))
+            .field("capacity", &self.capacity())
+            .finish()
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, PartialEq, Eq, Default)]
 pub struct SpecialHeader(u32);
@@ -214,6 +871,29 @@ impl SpecialHeader {
 
         Self(inner)
     }
+
+    /// Reads a special header word back out of its serialized bytes
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::SpecialHeader;
+    ///
+    /// let bytes: [u8; 4] = SpecialHeader::new(true, 2, 3).into();
+    /// let header = SpecialHeader::from_bytes(bytes);
+    ///
+    /// assert!(header.send_pid());
+    /// assert_eq!(header.num_copy_handles(), 2);
+    /// assert_eq!(header.num_move_handles(), 3);
+    /// ```
+    pub const fn from_bytes(bytes: [u8; 4]) -> Self {
+        Self(u32::from_le_bytes(bytes))
+    }
+}
+
+impl const From<[u8; 4]> for SpecialHeader {
+    fn from(value: [u8; 4]) -> Self {
+        Self(u32::from_le_bytes(value))
+    }
 }
 
 impl const From<SpecialHeader> for [u8; 4] {
@@ -222,6 +902,16 @@ impl const From<SpecialHeader> for [u8; 4] {
     }
 }
 
+impl core::fmt::Debug for SpecialHeader {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        f.debug_struct("SpecialHeader")
+            .field("send_pid", &self.send_pid())
+            .field("num_copy_handles", &self.num_copy_handles())
+            .field("num_move_handles", &self.num_move_handles())
+            .finish()
+    }
+}
+
 #[repr(C)]
 #[derive(Copy, Clone, PartialEq, Eq, Default)]
 pub struct Header([u32; 2]);
@@ -263,6 +953,130 @@ impl Header {
         extract(self.0[1], 31, 32) != 0
     }
 
+    /// The reserved bits between the receive static mode and the receive list offset, which
+    /// [`Header::new`] always leaves zeroed
+    pub const fn reserved(self) -> u32 {
+        extract(self.0[1], 14, 20)
+    }
+
+    /// The header words exactly as they are serialized
+    pub const fn words(self) -> [u32; 2] {
+        self.0
+    }
+
+    /// Constructs a header from its words verbatim, without packing any fields
+    ///
+    /// This is meant for testing and reproducing captured commands, where fields (including the
+    /// reserved bits) may need values that [`Header::new`] will never produce. Prefer
+    /// [`Header::new`] for anything else.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::Header;
+    ///
+    /// let clean = Header::new(4, 0, 1, 0, 0, 2, 0, 0, false);
+    /// assert_eq!(clean.reserved(), 0);
+    ///
+    /// // The same header with every reserved bit set
+    /// let [first, second] = clean.words();
+    /// let dirty = Header::new_raw([first, second | (0x3F << 14)]);
+    ///
+    /// assert_eq!(dirty.reserved(), 0x3F);
+    /// assert_ne!(dirty, clean);
+    /// assert_eq!(dirty.ty(), clean.ty());
+    /// assert_eq!(dirty.num_send_buffers(), clean.num_send_buffers());
+    /// assert_eq!(dirty.raw_data_len(), clean.raw_data_len());
+    /// assert_eq!(dirty.receive_static_mode(), clean.receive_static_mode());
+    /// assert_eq!(dirty.receive_list_offset(), clean.receive_list_offset());
+    /// ```
+    pub const fn new_raw(words: [u32; 2]) -> Self {
+        Self(words)
+    }
+
+    /// Reads a header back out of its serialized bytes
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::Header;
+    ///
+    /// let header = Header::new(4, 1, 2, 3, 4, 5, 2, 6, true);
+    /// let bytes: [u8; 8] = header.into();
+    /// let parsed = Header::from_bytes(bytes);
+    ///
+    /// assert_eq!(parsed, header);
+    /// assert_eq!(parsed.ty(), 4);
+    /// assert_eq!(parsed.num_send_statics(), 1);
+    /// assert_eq!(parsed.num_send_buffers(), 2);
+    /// assert_eq!(parsed.num_receive_buffers(), 3);
+    /// assert_eq!(parsed.num_exchange_buffers(), 4);
+    /// assert_eq!(parsed.raw_data_len(), 5);
+    /// assert_eq!(parsed.receive_static_mode(), 2);
+    /// assert_eq!(parsed.receive_list_offset(), 6);
+    /// assert!(parsed.has_special_header());
+    /// ```
+    pub const fn from_bytes(bytes: [u8; 8]) -> Self {
+        Self([
+            u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+            u32::from_le_bytes([bytes[4], bytes[5], bytes[6], bytes[7]])
+        ])
+    }
+
+    /// Replaces the command type, leaving every other bit of the header untouched
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{packed::Header, CommandType};
+    ///
+    /// let header = Header::new(4, 1, 2, 3, 4, 5, 2, 6, true);
+    /// let edited = header.with_type(CommandType::Control as u16);
+    ///
+    /// assert_eq!(edited.ty(), 5);
+    /// assert_eq!(edited.words()[0] & !0xFFFF, header.words()[0] & !0xFFFF);
+    /// assert_eq!(edited.words()[1], header.words()[1]);
+    /// ```
+    pub const fn with_type(self, ty: u16) -> Self {
+        Self([set(ty as u32, self.0[0], 0, 0, 16), self.0[1]])
+    }
+
+    /// Replaces the number of raw data words, leaving every other bit of the header untouched
+    ///
+    /// Only the low 10 bits of `raw_data_len` are kept, the same as [`Header::new`].
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::Header;
+    ///
+    /// let header = Header::new(4, 1, 2, 3, 4, 5, 2, 6, true);
+    /// let edited = header.with_raw_data_len(0x3FF);
+    ///
+    /// assert_eq!(edited.raw_data_len(), 0x3FF);
+    /// assert_eq!(edited.words()[0], header.words()[0]);
+    /// assert_eq!(edited.words()[1] & !0x3FF, header.words()[1] & !0x3FF);
+    /// ```
+    pub const fn with_raw_data_len(self, raw_data_len: usize) -> Self {
+        Self([self.0[0], set(raw_data_len as u32, self.0[1], 0, 0, 10)])
+    }
+
+    /// Replaces the receive list offset, leaving every other bit of the header untouched
+    ///
+    /// Only the low 11 bits of `receive_list_offset` are kept, the same as [`Header::new`].
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::packed::Header;
+    ///
+    /// let header = Header::new(4, 1, 2, 3, 4, 5, 2, 6, true);
+    /// let edited = header.with_receive_list_offset(0x40);
+    ///
+    /// assert_eq!(edited.receive_list_offset(), 0x40);
+    /// assert_eq!(edited.words()[0], header.words()[0]);
+    /// assert_eq!(edited.words()[1] & !(0x7FF << 20), header.words()[1] & !(0x7FF << 20));
+    /// assert!(edited.has_special_header());
+    /// ```
+    pub const fn with_receive_list_offset(self, receive_list_offset: usize) -> Self {
+        Self([self.0[0], set(receive_list_offset as u32, self.0[1], 0, 20, 11)])
+    }
+
     #[allow(clippy::too_many_arguments)]
     pub const fn new(
         ty: u16,
@@ -288,6 +1102,103 @@ impl Header {
 
         Self([first, second])
     }
+
+    /// The total length, in bytes, of the command this header describes
+    ///
+    /// This covers every section the header declares, including the receive list implied by
+    /// the receive static mode. When the receive list offset is set, the receive list is
+    /// placed there (which is how the inline buffer of receive static mode `1` is accounted
+    /// for), otherwise it directly follows the raw data. An offset that points before the end
+    /// of the raw data is treated as pointing at its end, so every declared section is within
+    /// the returned length.
+    ///
+    /// # Arguments
+    /// * `special_header_total` - The number of bytes the special header consumes (0 if not
+    ///   present), see [`SpecialHeaderView::byte_len`](crate::header::SpecialHeaderView::byte_len)
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, header::{self, SpecialHeaderView}, packed::{BufferDescriptor, Header, ReceiveListEntry}};
+    ///
+    /// let parse = |bytes: &[u8]| Header::new_raw([
+    ///     u32::from_le_bytes(bytes[0..4].try_into().unwrap()),
+    ///     u32::from_le_bytes(bytes[4..8].try_into().unwrap())
+    /// ]);
+    ///
+    /// // Receive static mode 0, no receive list
+    /// let bytes = command::request_builder()
+    ///     .with_send_buffer(BufferDescriptor::new(0x1000, 0x100, 0))
+    ///     .with_raw_data([1u32, 2])
+    ///     .build();
+    /// assert_eq!(parse(&bytes).receive_static_mode(), 0);
+    /// assert_eq!(parse(&bytes).total_len(0), bytes.len());
+    ///
+    /// // Receive static mode 1, an inline buffer ending where the receive list offset points
+    /// let header = Header::new(4, 0, 0, 0, 0, 2, 1, 0x10, false);
+    /// assert_eq!(header.total_len(0), 0x40);
+    ///
+    /// // Receive static mode 2, a single pointer buffer entry
+    /// let bytes = command::request_builder()
+    ///     .with_raw_data([1u32])
+    ///     .with_pointer_buffer_sized(0x2000, 0x100)
+    ///     .build();
+    /// assert_eq!(parse(&bytes).receive_static_mode(), 2);
+    /// assert_eq!(parse(&bytes).total_len(0), bytes.len());
+    ///
+    /// // Receive static mode 5, three receive list entries after a special header
+    /// let special = header::new_builder().with_copy_handle(0xFFFF_8001);
+    /// let bytes = command::request_builder()
+    ///     .with_special_header(special)
+    ///     .with_recv_statics([
+    ///         ReceiveListEntry::new(0x1000, 0x10),
+    ///         ReceiveListEntry::new(0x2000, 0x20),
+    ///         ReceiveListEntry::new(0x3000, 0x30)
+    ///     ])
+    ///     .build();
+    /// let special_header_total = SpecialHeaderView::from_bytes(&bytes[8..]).unwrap().byte_len();
+    /// assert_eq!(parse(&bytes).receive_static_mode(), 5);
+    /// assert_eq!(parse(&bytes).total_len(special_header_total), bytes.len());
+    ///
+    /// // Slicing the first command out of a stream of two
+    /// let first = command::request_builder().with_raw_data([0xAAu32]).build();
+    /// let second = command::control_builder().with_raw_data([0xBBu32, 0xCC]).build();
+    /// let stream = [&first[..], &second[..]].concat();
+    ///
+    /// let len = parse(&stream).total_len(0);
+    /// assert_eq!(&stream[..len], &first);
+    /// assert_eq!(parse(&stream[len..]).total_len(0), second.len());
+    ///
+    /// // A receive list offset pointing into the raw data still covers all 8 words of it
+    /// let header = Header::new(4, 0, 0, 0, 0, 8, 2, 2, false);
+    /// assert_eq!(header.total_len(0), 8 + 8 * 4 + 8);
+    /// ```
+    pub const fn total_len(self, special_header_total: usize) -> usize {
+        let mode = self.receive_static_mode() as usize;
+        let recv_statics = mode.saturating_sub(2);
+        let has_pointer_buffer = mode == 2;
+
+        let offsets = crate::command::section_offsets(
+            self.num_send_statics(),
+            self.num_send_buffers(),
+            self.num_receive_buffers(),
+            self.num_exchange_buffers(),
+            recv_statics,
+            self.raw_data_len(),
+            0,
+            special_header_total,
+            has_pointer_buffer
+        );
+
+        if self.receive_list_offset() == 0 {
+            return offsets.end;
+        }
+
+        // A receive list offset that points back into the raw data cannot shorten the command
+        let list_start = self.receive_list_offset() * core::mem::size_of::<u32>();
+        let list_start = if list_start > offsets.inline_buffer { list_start } else { offsets.inline_buffer };
+
+        list_start + (offsets.end - offsets.pointer_buffer)
+    }
 }
 
 impl const From<Header> for [u8; 8] {
@@ -306,4 +1217,51 @@ impl const From<Header> for [u8; 8] {
 
         out
     }
-}
\ No newline at end of file
+}
+
+/// Prints the decoded fields rather than the packed words
+///
+/// The command type is printed by name when it is a known [`CommandType`](crate::CommandType),
+/// and the reserved bits are only printed when some are set.
+///
+/// # Example
+/// ```
+/// use hipc::packed::Header;
+///
+/// let header = Header::new(4, 1, 0, 0, 0, 3, 0, 0, true);
+/// assert_eq!(
+///     format!("{header:?}"),
+///     "Header { ty: Request, num_send_statics: 1, num_send_buffers: 0, num_receive_buffers: 0, \
+///      num_exchange_buffers: 0, raw_data_len: 3, receive_static_mode: 0, receive_list_offset: 0, \
+///      has_special_header: true }"
+/// );
+///
+/// let header = Header::new_raw([0xFF, 0x3F << 14]);
+/// assert!(format!("{header:?}").starts_with("Header { ty: 255, "));
+/// assert!(format!("{header:?}").ends_with("reserved: 0x3f }"));
+/// ```
+impl core::fmt::Debug for Header {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let mut out = f.debug_struct("Header");
+
+        match crate::CommandType::try_from(self.ty()) {
+            Ok(ty) => out.field("ty", &ty),
+            Err(_) => out.field("ty", &self.ty())
+        };
+
+        out.field("num_send_statics", &self.num_send_statics())
+            .field("num_send_buffers", &self.num_send_buffers())
+            .field("num_receive_buffers", &self.num_receive_buffers())
+            .field("num_exchange_buffers", &self.num_exchange_buffers())
+            .field("raw_data_len", &self.raw_data_len())
+            .field("receive_static_mode", &self.receive_static_mode())
+            .field("receive_list_offset", &self.receive_list_offset())
+            .field("has_special_header", &self.has_special_header());
+
+        if self.reserved() != 0 {
+            out.field("reserved", &Hex(self.reserved() as u64));
+        }
+
+        out.finish()
+    }
+}
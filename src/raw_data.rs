@@ -0,0 +1,155 @@
+use crate::util::const_assert;
+
+/// Builder for a raw data payload made up of ordered chunks, each with its own alignment
+///
+/// # Generics
+/// * `OFFSET` - The byte offset, from the start of the command, that the raw data begins at
+/// * `LEN` - The number of 32-bit words the payload currently consumes, including padding
+///
+/// Alignment is computed relative to the start of the command rather than the start of the
+/// raw data, since that is what protocols layered on top of HIPC (such as CMIF) align to. The
+/// builder converts into a `[u32; LEN]`, so it can be passed directly to
+/// [`with_raw_data`](crate::command::HipcCommandBuilder::with_raw_data). To construct a new,
+/// empty builder, you should use [`new_builder`].
+///
+/// # Example
+/// The following lays out the raw data for a domain request carrying two input objects, in
+/// a command with no descriptors (so the raw data begins right after the 8-byte header).
+/// ```
+/// use hipc::raw_data;
+///
+/// const DOMAIN_HEADER: [u32; 4] = [0x0018_0201, 0x0000_0001, 0, 0];
+/// const CMIF_HEADER: [u32; 4] = [u32::from_le_bytes(*b"SFCI"), 0, 42, 0];
+///
+/// let words: [u32; 14] = raw_data::new_builder::<8>()
+///     .with_header(DOMAIN_HEADER)
+///     .with_header(CMIF_HEADER)
+///     .with_args([0xAAAA_AAAA, 0xBBBB_BBBB])
+///     .with_in_objects([5, 6])
+///     .into();
+///
+/// // Two words of padding to reach the 16-byte aligned offset 0x10
+/// assert_eq!(&words[..2], &[0, 0]);
+/// assert_eq!(&words[2..6], &DOMAIN_HEADER);
+/// assert_eq!(&words[6..10], &CMIF_HEADER);
+/// assert_eq!(&words[10..12], &[0xAAAA_AAAA, 0xBBBB_BBBB]);
+/// assert_eq!(&words[12..], &[5, 6]);
+/// ```
+#[derive(Copy, Clone)]
+pub struct RawDataBuilder<const OFFSET: usize, const LEN: usize> {
+    words: [u32; LEN]
+}
+
+/// The alignment, in bytes, used for header chunks
+const HEADER_ALIGNMENT: usize = 0x10;
+
+/// The alignment, in bytes, used for argument and object ID chunks
+const WORD_ALIGNMENT: usize = core::mem::size_of::<u32>();
+
+#[doc(hidden)]
+pub const fn chunk_end(offset: usize, len: usize, align: usize, chunk_len: usize) -> usize {
+    const_assert!(
+        align != 0 && align % WORD_ALIGNMENT == 0,
+        "Raw data chunks must be aligned to a nonzero multiple of the word size!",
+        "align" => align
+    );
+
+    let current = offset + len * WORD_ALIGNMENT;
+    let aligned = crate::command::helpers::align_up(current, align);
+
+    len + (aligned - current) / WORD_ALIGNMENT + chunk_len
+}
+
+impl<const OFFSET: usize> RawDataBuilder<OFFSET, 0> {
+    /// Constructs a new, empty raw data payload.
+    ///
+    /// This only exists on empty payloads, so that a builder type with words already in it
+    /// cannot be used to construct one. It is usually easier to call [`new_builder`].
+    pub const fn new() -> Self {
+        RawDataBuilder {
+            words: []
+        }
+    }
+}
+
+impl<const OFFSET: usize> Default for RawDataBuilder<OFFSET, 0> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const OFFSET: usize, const LEN: usize> RawDataBuilder<OFFSET, LEN> {
+
+    /// Appends a chunk to the payload, padding with zeroes so that it begins at an `ALIGN`-byte
+    /// aligned offset in the command
+    ///
+    /// # Arguments
+    /// * `chunk` - The words to append
+    ///
+    /// # Failures
+    /// * `ALIGN` is not a nonzero multiple of 4
+    pub const fn with_chunk<const ALIGN: usize, const N: usize>(self, chunk: [u32; N]) -> RawDataBuilder<OFFSET, { chunk_end(OFFSET, LEN, ALIGN, N) }> {
+        let mut words = [0u32; chunk_end(OFFSET, LEN, ALIGN, N)];
+
+        let mut index = 0;
+        while index < LEN {
+            words[index] = self.words[index];
+            index += 1;
+        }
+
+        let start = chunk_end(OFFSET, LEN, ALIGN, N) - N;
+        index = 0;
+        while index < N {
+            words[start + index] = chunk[index];
+            index += 1;
+        }
+
+        RawDataBuilder {
+            words
+        }
+    }
+
+    /// Appends a header (such as a CMIF or domain header) to the payload, aligned to 16 bytes
+    pub const fn with_header<const N: usize>(self, header: [u32; N]) -> RawDataBuilder<OFFSET, { chunk_end(OFFSET, LEN, HEADER_ALIGNMENT, N) }> {
+        self.with_chunk::<HEADER_ALIGNMENT, N>(header)
+    }
+
+    /// Appends command arguments to the payload, directly after the previous chunk
+    pub const fn with_args<const N: usize>(self, args: [u32; N]) -> RawDataBuilder<OFFSET, { chunk_end(OFFSET, LEN, WORD_ALIGNMENT, N) }> {
+        self.with_chunk::<WORD_ALIGNMENT, N>(args)
+    }
+
+    /// Appends a list of domain input object IDs to the payload, directly after the previous chunk
+    pub const fn with_in_objects<const N: usize>(self, object_ids: [u32; N]) -> RawDataBuilder<OFFSET, { chunk_end(OFFSET, LEN, WORD_ALIGNMENT, N) }> {
+        self.with_chunk::<WORD_ALIGNMENT, N>(object_ids)
+    }
+}
+
+impl<const OFFSET: usize, const LEN: usize> const From<RawDataBuilder<OFFSET, LEN>> for [u32; LEN] {
+    fn from(value: RawDataBuilder<OFFSET, LEN>) -> Self {
+        value.words
+    }
+}
+
+impl<const OFFSET: usize, const LEN: usize> const crate::IntoWords<LEN> for RawDataBuilder<OFFSET, LEN> {}
+
+/// Constructs a new [`RawDataBuilder`] for raw data beginning at byte `OFFSET` of the command
+///
+/// # Example
+/// ```
+/// use hipc::{cmif, command, raw_data};
+///
+/// const OFFSET: usize = cmif::layout(0, 0, 0, 0, 0, false).raw_data;
+///
+/// let bytes = command::request_builder()
+///     .with_raw_data(
+///         raw_data::new_builder::<OFFSET>()
+///             .with_header([u32::from_le_bytes(*b"SFCI"), 0, 1, 0])
+///     )
+///     .build();
+///
+/// assert_eq!(&bytes[0x10..0x14], b"SFCI");
+/// ```
+pub const fn new_builder<const OFFSET: usize>() -> RawDataBuilder<OFFSET, 0> {
+    RawDataBuilder::<OFFSET, 0>::new()
+}
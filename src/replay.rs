@@ -0,0 +1,145 @@
+//! An escape hatch for replay/fuzzing
+//!
+//! Nothing in this module validates its input. It exists so that captured or deliberately
+//! malformed commands can be reproduced word for word, which the builder in
+//! [`crate::command`] will (rightfully) refuse to produce. Prefer the builder for anything
+//! that is meant to be a well-formed command.
+
+use crate::util::read_word;
+
+/// The size of the two header words, in bytes
+const HEADER_SIZE: usize = 2 * core::mem::size_of::<u32>();
+
+/// A command made up of two raw header words followed by an arbitrary body
+///
+/// The body is everything after the header (special header, descriptors, raw data, receive
+/// list) exactly as it should appear on the wire.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RawCommand<'a> {
+    /// The two header words, written as-is
+    pub header: [u32; 2],
+
+    /// The bytes following the header, written as-is
+    pub body: &'a [u8]
+}
+
+impl<'a> RawCommand<'a> {
+    /// Creates a raw command from its header words and body
+    ///
+    /// # Arguments
+    /// * `header` - The two header words
+    /// * `body` - The bytes following the header
+    pub const fn new(header: [u32; 2], body: &'a [u8]) -> Self {
+        Self { header, body }
+    }
+
+    /// Splits a captured command into its header words and body
+    ///
+    /// # Arguments
+    /// * `bytes` - The captured command
+    ///
+    /// # Returns
+    /// * `None` if `bytes` is too short to hold the header words
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::{command, replay::RawCommand};
+    ///
+    /// let built = command::request_builder().with_raw_data([0xDEAD_BEEFu32]).build();
+    /// let raw = RawCommand::from_bytes(&built).unwrap();
+    /// assert_eq!(raw.header, [0x4, 0x1]);
+    /// assert_eq!(raw.body, &0xDEAD_BEEFu32.to_le_bytes());
+    ///
+    /// assert!(RawCommand::from_bytes(&[0; 7]).is_none());
+    /// ```
+    pub const fn from_bytes(bytes: &'a [u8]) -> Option<Self> {
+        if bytes.len() < HEADER_SIZE {
+            return None;
+        }
+
+        let (_, body) = bytes.split_at(HEADER_SIZE);
+
+        Some(Self {
+            header: [read_word(bytes, 0), read_word(bytes, 4)],
+            body
+        })
+    }
+
+    /// The size of the serialized command, in bytes
+    pub const fn len(&self) -> usize {
+        HEADER_SIZE + self.body.len()
+    }
+
+    /// Always `false`, a raw command contains at least its header words
+    pub const fn is_empty(&self) -> bool {
+        false
+    }
+
+    /// Serializes the command into a fixed size array, zero filling anything past the body
+    ///
+    /// # Panicking
+    /// * `N` is smaller than [`RawCommand::len`]
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::replay::RawCommand;
+    ///
+    /// // A request claiming three raw data words while only carrying one
+    /// let raw = RawCommand::new([0x4, 0x3], &[0xAA, 0xBB, 0xCC, 0xDD]);
+    /// assert_eq!(raw.serialize::<16>(), [
+    ///     0x04, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00,
+    ///     0xAA, 0xBB, 0xCC, 0xDD, 0x00, 0x00, 0x00, 0x00
+    /// ]);
+    /// ```
+    ///
+    /// ```should_panic
+    /// use hipc::replay::RawCommand;
+    ///
+    /// let _ = RawCommand::new([0x4, 0x0], &[0; 4]).serialize::<8>();
+    /// ```
+    #[track_caller]
+    pub const fn serialize<const N: usize>(&self) -> [u8; N] {
+        if N < self.len() {
+            crate::util::panic_with_value("Raw command does not fit in the output, bytes needed: ", self.len());
+        }
+
+        let mut out = [0u8; N];
+
+        let first = self.header[0].to_le_bytes();
+        let second = self.header[1].to_le_bytes();
+
+        let mut index = 0;
+        while index < 4 {
+            out[index] = first[index];
+            out[index + 4] = second[index];
+            index += 1;
+        }
+
+        let mut index = 0;
+        while index < self.body.len() {
+            out[HEADER_SIZE + index] = self.body[index];
+            index += 1;
+        }
+
+        out
+    }
+
+    /// Serializes the command into the start of `dst`
+    ///
+    /// # Returns
+    /// * The number of bytes written, which is always [`RawCommand::len`]
+    ///
+    /// # Panicking
+    /// * `dst` is shorter than [`RawCommand::len`]
+    pub fn write_into(&self, dst: &mut [u8]) -> usize {
+        if dst.len() < self.len() {
+            panic!("Destination is too small for the command!");
+        }
+
+        dst[..4].copy_from_slice(&self.header[0].to_le_bytes());
+        dst[4..HEADER_SIZE].copy_from_slice(&self.header[1].to_le_bytes());
+        dst[HEADER_SIZE..self.len()].copy_from_slice(self.body);
+
+        self.len()
+    }
+}
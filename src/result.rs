@@ -0,0 +1,128 @@
+/// The number of bits the module takes up at the bottom of a result code
+const MODULE_BITS: u32 = 9;
+
+/// The number of bits the description takes up directly above the module
+const DESCRIPTION_BITS: u32 = 13;
+
+/// The module kernel results are reported under
+const KERNEL_MODULE: u32 = 1;
+
+/// A Horizon result code, as returned by SVCs and in IPC responses
+///
+/// # Memory Layout
+/// | Bits | Purpose |
+/// | ---- | ------- |
+/// | `0..9` | Module |
+/// | `9..22` | Description |
+#[repr(transparent)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct ResultCode(u32);
+
+impl ResultCode {
+    /// The operation succeeded
+    pub const SUCCESS: ResultCode = ResultCode(0);
+
+    /// The kernel has no memory left to satisfy the request
+    pub const OUT_OF_MEMORY: ResultCode = ResultCode::new(KERNEL_MODULE, 104);
+
+    /// The requested functionality is not implemented
+    pub const NOT_IMPLEMENTED: ResultCode = ResultCode::new(KERNEL_MODULE, 33);
+
+    /// The other side of the session was closed
+    pub const SESSION_CLOSED: ResultCode = ResultCode::new(KERNEL_MODULE, 123);
+
+    /// The same code as [`ResultCode::SESSION_CLOSED`], under the name libnx uses for it
+    pub const CONNECTION_CLOSED: ResultCode = ResultCode::SESSION_CLOSED;
+
+    /// The port being connected to was closed by its owner
+    pub const PORT_REMOTE_DEAD: ResultCode = ResultCode::new(KERNEL_MODULE, 131);
+
+    /// Constructs a result code from its module and description
+    ///
+    /// Both values are truncated to the width of their fields.
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::result::ResultCode;
+    ///
+    /// assert_eq!(ResultCode::SUCCESS.raw(), 0);
+    /// assert_eq!(ResultCode::OUT_OF_MEMORY.raw(), 0xD001);
+    /// assert_eq!(ResultCode::NOT_IMPLEMENTED.raw(), 0x4201);
+    /// assert_eq!(ResultCode::SESSION_CLOSED.raw(), 0xF601);
+    /// assert_eq!(ResultCode::CONNECTION_CLOSED.raw(), 0xF601);
+    /// assert_eq!(ResultCode::PORT_REMOTE_DEAD.raw(), 0x10601);
+    /// ```
+    pub const fn new(module: u32, description: u32) -> Self {
+        let module = module & ((1 << MODULE_BITS) - 1);
+        let description = description & ((1 << DESCRIPTION_BITS) - 1);
+
+        Self(module | (description << MODULE_BITS))
+    }
+
+    /// Wraps a raw result code
+    pub const fn from_raw(raw: u32) -> Self {
+        Self(raw)
+    }
+
+    /// The raw result code
+    pub const fn raw(self) -> u32 {
+        self.0
+    }
+
+    /// The module the result was reported by
+    pub const fn module(self) -> u32 {
+        self.0 & ((1 << MODULE_BITS) - 1)
+    }
+
+    /// The module-specific description of the result
+    pub const fn description(self) -> u32 {
+        (self.0 >> MODULE_BITS) & ((1 << DESCRIPTION_BITS) - 1)
+    }
+
+    /// If the result code represents success
+    pub const fn is_success(self) -> bool {
+        self.0 == 0
+    }
+
+    /// The name of the result, if it is one of the well known codes defined on this type
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::result::ResultCode;
+    ///
+    /// assert_eq!(ResultCode::from_raw(0xF601).description_name(), Some("SessionClosed"));
+    /// assert_eq!(ResultCode::new(2, 1).description_name(), None);
+    /// ```
+    pub const fn description_name(&self) -> Option<&'static str> {
+        if self.0 == ResultCode::SUCCESS.0 {
+            Some("Success")
+        } else if self.0 == ResultCode::OUT_OF_MEMORY.0 {
+            Some("OutOfMemory")
+        } else if self.0 == ResultCode::NOT_IMPLEMENTED.0 {
+            Some("NotImplemented")
+        } else if self.0 == ResultCode::SESSION_CLOSED.0 {
+            Some("SessionClosed")
+        } else if self.0 == ResultCode::PORT_REMOTE_DEAD.0 {
+            Some("PortRemoteDead")
+        } else {
+            None
+        }
+    }
+}
+
+/// Formats the result code in the conventional `2XXX-YYYY` form, where `XXX` is the module
+/// offset by 2000 and `YYYY` is the zero-padded description
+///
+/// # Example
+/// ```
+/// use hipc::result::ResultCode;
+///
+/// assert_eq!(ResultCode::new(168, 2).to_string(), "2168-0002");
+/// assert_eq!(ResultCode::SESSION_CLOSED.to_string(), "2001-0123");
+/// assert_eq!(ResultCode::SUCCESS.to_string(), "2000-0000");
+/// ```
+impl core::fmt::Display for ResultCode {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "{}-{:04}", 2000 + self.module(), self.description())
+    }
+}
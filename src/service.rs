@@ -0,0 +1,143 @@
+//! Tables of known service commands
+//!
+//! Each submodule describes one service: the IDs of its commands and any helpers needed to
+//! encode their arguments. The [`service_command!`] macro uses these tables to produce a
+//! correctly shaped [`HipcCommandBuilder`](crate::command::HipcCommandBuilder) in a single
+//! call.
+
+/// The service manager (`sm:`), used to open sessions to every other service
+pub mod sm {
+    use crate::util::const_assert;
+
+    /// The command ID for `RegisterClient`
+    pub const REGISTER_CLIENT: u32 = 0;
+
+    /// The command ID for `GetService`
+    pub const GET_SERVICE: u32 = 1;
+
+    /// The command ID for `UnregisterService`
+    pub const UNREGISTER_SERVICE: u32 = 3;
+
+    /// The maximum length of a service name, in bytes
+    pub const MAX_NAME_LEN: usize = core::mem::size_of::<u64>();
+
+    /// Encodes a service name as the two argument words `sm` expects
+    ///
+    /// Names are packed into a single 64-bit value, padded with zeroes.
+    ///
+    /// # Panicking
+    /// * `name` is longer than [`MAX_NAME_LEN`] bytes
+    ///
+    /// # Example
+    /// ```
+    /// use hipc::service::sm;
+    ///
+    /// let words = sm::service_name("fsp-srv");
+    /// assert_eq!(words, [u32::from_le_bytes(*b"fsp-"), u32::from_le_bytes(*b"srv\0")]);
+    /// ```
+    #[track_caller]
+    pub const fn service_name(name: &str) -> [u32; 2] {
+        let bytes = name.as_bytes();
+        const_assert!(bytes.len() <= MAX_NAME_LEN, "Service names cannot be longer than 8 bytes!", "length" => bytes.len());
+
+        let mut packed = [0u8; MAX_NAME_LEN];
+        let mut index = 0;
+        while index < bytes.len() {
+            packed[index] = bytes[index];
+            index += 1;
+        }
+
+        [
+            u32::from_le_bytes([packed[0], packed[1], packed[2], packed[3]]),
+            u32::from_le_bytes([packed[4], packed[5], packed[6], packed[7]])
+        ]
+    }
+}
+
+/// Constructs the builder for a known service command
+///
+/// The command ID, special header, and raw data layout are all taken from the tables in
+/// [`crate::service`], so the result only needs to be built (or extended with anything the
+/// caller wants to add).
+///
+/// # Supported Commands
+/// | Invocation | Arguments |
+/// | ---------- | --------- |
+/// | `service_command!(sm::RegisterClient)` | None, the process ID is filled in by the kernel |
+/// | `service_command!(sm::GetService, name)` | The service name, as a `&str` |
+/// | `service_command!(sm::UnregisterService, name)` | The service name, as a `&str` |
+///
+/// # Example
+/// ```
+/// #![feature(const_trait_impl)]
+/// use hipc::service;
+///
+/// const GET_FSP: [u8; 40] = service::service_command!(sm::GetService, "fsp-srv").build();
+///
+/// assert_eq!(GET_FSP, [
+///     0x04, 0x00, 0x00, 0x00, 0x08, 0x00, 0x00, 0x00, // Request, 8 words of raw data
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Padding to the CMIF header
+///     b'S', b'F', b'C', b'I', 0x00, 0x00, 0x00, 0x00, // CMIF magic
+///     0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Command ID 1 (GetService), token
+///     b'f', b's', b'p', b'-', b's', b'r', b'v', 0x00  // Service name
+/// ]);
+///
+/// let register = service::service_command!(sm::RegisterClient).build();
+/// assert_eq!(register, [
+///     0x04, 0x00, 0x00, 0x00, 0x09, 0x00, 0x00, 0x80, // Request, 9 words of raw data, special header
+///     0x01, 0x00, 0x00, 0x00,                         // Special header sending the process ID
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Process ID placeholder
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Padding to the CMIF header
+///     0x00, 0x00, 0x00, 0x00,
+///     b'S', b'F', b'C', b'I', 0x00, 0x00, 0x00, 0x00, // CMIF magic
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Command ID 0 (RegisterClient), token
+///     0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00  // Reserved argument
+/// ]);
+///
+/// let unregister = service::service_command!(sm::UnregisterService, "fsp-srv").build();
+/// assert_eq!(&unregister[0x18..0x1C], &3u32.to_le_bytes());
+/// assert_eq!(&unregister[0x20..], b"fsp-srv\0");
+/// ```
+///
+/// Commands missing from the tables are rejected at compile time.
+/// ```compile_fail
+/// use hipc::service;
+///
+/// let _ = service::service_command!(sm::RegisterService, "fsp-srv");
+/// ```
+#[macro_export]
+macro_rules! service_command {
+    (sm::RegisterClient $(,)?) => {
+        $crate::command::request_builder()
+            .with_special_header($crate::header::new_builder().with_program_id(0))
+            .with_raw_data(
+                $crate::raw_data::new_builder::<{ $crate::cmif::layout(0, 0, 0, 0, $crate::header::consumed_space(1, 0, 0), false).raw_data }>()
+                    .with_header([$crate::cmif::REQUEST_MAGIC, 0, $crate::service::sm::REGISTER_CLIENT, 0])
+                    .with_args([0u32, 0])
+            )
+    };
+
+    (sm::GetService, $name:expr $(,)?) => {
+        $crate::command::request_builder()
+            .with_raw_data(
+                $crate::raw_data::new_builder::<{ $crate::cmif::layout(0, 0, 0, 0, 0, false).raw_data }>()
+                    .with_header([$crate::cmif::REQUEST_MAGIC, 0, $crate::service::sm::GET_SERVICE, 0])
+                    .with_args($crate::service::sm::service_name($name))
+            )
+    };
+
+    (sm::UnregisterService, $name:expr $(,)?) => {
+        $crate::command::request_builder()
+            .with_raw_data(
+                $crate::raw_data::new_builder::<{ $crate::cmif::layout(0, 0, 0, 0, 0, false).raw_data }>()
+                    .with_header([$crate::cmif::REQUEST_MAGIC, 0, $crate::service::sm::UNREGISTER_SERVICE, 0])
+                    .with_args($crate::service::sm::service_name($name))
+            )
+    };
+
+    ($($unknown:tt)*) => {
+        compile_error!(concat!("Unknown service command: ", stringify!($($unknown)*)))
+    };
+}
+
+pub use service_command;
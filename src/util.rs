@@ -0,0 +1,270 @@
+/// Reads a little-endian word out of `bytes` at `offset`
+pub(crate) const fn read_word(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes([bytes[offset], bytes[offset + 1], bytes[offset + 2], bytes[offset + 3]])
+}
+
+/// The size of the buffer panic messages are rendered into
+const MAX_PANIC_MESSAGE_LEN: usize = 192;
+
+/// Appends `bytes` to `buffer` at `len`, dropping anything that does not fit
+///
+/// # Returns
+/// * The new length of the message in `buffer`
+const fn push_bytes(buffer: &mut [u8; MAX_PANIC_MESSAGE_LEN], mut len: usize, bytes: &[u8], limit: usize) -> usize {
+    let mut index = 0;
+    while index < bytes.len() && len < limit {
+        buffer[len] = bytes[index];
+        len += 1;
+        index += 1;
+    }
+
+    len
+}
+
+/// Appends `value` written in decimal to `buffer` at `len`
+///
+/// # Returns
+/// * The new length of the message in `buffer`
+const fn push_decimal(buffer: &mut [u8; MAX_PANIC_MESSAGE_LEN], mut len: usize, value: usize) -> usize {
+    const MAX_DIGITS: usize = 20;
+
+    let mut digits = [0u8; MAX_DIGITS];
+    let mut num_digits = 0;
+    let mut remaining = value;
+    loop {
+        digits[num_digits] = b'0' + (remaining % 10) as u8;
+        num_digits += 1;
+        remaining /= 10;
+        if remaining == 0 {
+            break;
+        }
+    }
+
+    while num_digits > 0 && len < MAX_PANIC_MESSAGE_LEN {
+        num_digits -= 1;
+        buffer[len] = digits[num_digits];
+        len += 1;
+    }
+
+    len
+}
+
+/// Panics with the first `len` bytes of `buffer`, or with `fallback` if they are not valid UTF-8
+#[track_caller]
+const fn panic_with_buffer(buffer: &[u8; MAX_PANIC_MESSAGE_LEN], len: usize, fallback: &str) -> ! {
+    let (bytes, _) = buffer.split_at(len);
+
+    match core::str::from_utf8(bytes) {
+        Ok(message) => panic!("{}", message),
+        // The message was truncated in the middle of a UTF-8 sequence
+        Err(_) => panic!("{}", fallback)
+    }
+}
+
+/// Panics with `message` followed by `value` written in decimal
+///
+/// Formatting arguments other than a single `&str` are not supported by `panic!` in const
+/// contexts, so this renders the value by hand.
+///
+/// # Arguments
+/// * `message` - The start of the panic message, truncated if it is longer than 96 bytes
+/// * `value` - The value to append to the message
+#[doc(hidden)]
+#[track_caller]
+pub const fn panic_with_value(message: &str, value: usize) -> ! {
+    const MAX_MESSAGE_LEN: usize = 96;
+
+    let mut buffer = [0u8; MAX_PANIC_MESSAGE_LEN];
+    let len = push_bytes(&mut buffer, 0, message.as_bytes(), MAX_MESSAGE_LEN);
+    let len = push_decimal(&mut buffer, len, value);
+
+    panic_with_buffer(&buffer, len, message)
+}
+
+/// Panics with `message` followed by a list of labelled values, such as
+/// `message (first: 1, second: 2)`
+///
+/// This is the multi-value counterpart to [`panic_with_value`], with the whole message
+/// truncated to 192 bytes.
+///
+/// # Arguments
+/// * `message` - The start of the panic message
+/// * `fields` - The labels and values to list after the message
+#[doc(hidden)]
+#[track_caller]
+pub const fn panic_with_values(message: &str, fields: &[(&str, usize)]) -> ! {
+    let mut buffer = [0u8; MAX_PANIC_MESSAGE_LEN];
+    let mut len = push_bytes(&mut buffer, 0, message.as_bytes(), MAX_PANIC_MESSAGE_LEN);
+    len = push_bytes(&mut buffer, len, b" (", MAX_PANIC_MESSAGE_LEN);
+
+    let mut index = 0;
+    while index < fields.len() {
+        if index != 0 {
+            len = push_bytes(&mut buffer, len, b", ", MAX_PANIC_MESSAGE_LEN);
+        }
+
+        len = push_bytes(&mut buffer, len, fields[index].0.as_bytes(), MAX_PANIC_MESSAGE_LEN);
+        len = push_bytes(&mut buffer, len, b": ", MAX_PANIC_MESSAGE_LEN);
+        len = push_decimal(&mut buffer, len, fields[index].1);
+        index += 1;
+    }
+
+    len = push_bytes(&mut buffer, len, b")", MAX_PANIC_MESSAGE_LEN);
+
+    panic_with_buffer(&buffer, len, message)
+}
+
+/// Panics unless a condition holds, listing the values involved in the message
+///
+/// Const panics only accept a single `&str`, so values are listed after the message as
+/// `"label" => value` pairs and rendered with [`panic_with_values`]. Every value is converted
+/// with `as usize`.
+macro_rules! const_assert {
+    ($cond:expr, $message:literal $(,)?) => {
+        if !$cond {
+            panic!($message);
+        }
+    };
+
+    ($cond:expr, $message:expr $(,)?) => {
+        if !$cond {
+            panic!("{}", $message);
+        }
+    };
+
+    ($cond:expr, $message:expr, $($label:literal => $value:expr),+ $(,)?) => {
+        if !$cond {
+            $crate::util::panic_with_values($message, &[$(($label, $value as usize)),+]);
+        }
+    };
+}
+
+pub(crate) use const_assert;
+
+/// Compares two byte slices, panicking with the index of the first difference when they
+/// do not match
+///
+/// Because this is a `const fn`, it can be used to check serialized commands at compile time.
+///
+/// # Arguments
+/// * `actual` - The bytes that were produced
+/// * `expected` - The bytes that were expected
+///
+/// # Panicking
+/// * The slices differ in any byte, or in length
+///
+/// # Example
+/// ```
+/// #![feature(const_trait_impl)]
+/// use hipc::{command, header, packed::BufferDescriptor, util::assert_bytes_eq, CommandType};
+///
+/// const CLOSE: [u8; 8] = command::new_builder(CommandType::Close).build();
+/// const _: () = assert_bytes_eq(&CLOSE, &[0x02, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+///
+/// const RAW_DATA: [u8; 12] = command::request_builder()
+///     .with_raw_data([0x1122_3344u32])
+///     .build();
+/// const _: () = assert_bytes_eq(&RAW_DATA, &[
+///     0x04, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00,
+///     0x44, 0x33, 0x22, 0x11
+/// ]);
+///
+/// const SPECIAL_HEADER: [u8; 16] = command::request_builder()
+///     .with_special_header(header::new_builder().with_copy_handle(0xFFFF_8001))
+///     .build();
+/// const _: () = assert_bytes_eq(&SPECIAL_HEADER, &[
+///     0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x80,
+///     0x02, 0x00, 0x00, 0x00,
+///     0x01, 0x80, 0xFF, 0xFF
+/// ]);
+///
+/// const SEND_BUFFER: [u8; 20] = command::request_builder()
+///     .with_send_buffer(BufferDescriptor::new(0x1234_5678, 0x100, 0))
+///     .build();
+/// const _: () = assert_bytes_eq(&SEND_BUFFER, &[
+///     0x04, 0x00, 0x10, 0x00, 0x00, 0x00, 0x00, 0x00,
+///     0x00, 0x01, 0x00, 0x00,
+///     0x78, 0x56, 0x34, 0x12,
+///     0x00, 0x00, 0x00, 0x00
+/// ]);
+/// ```
+///
+/// A mismatch fails to compile:
+/// ```compile_fail
+/// #![feature(const_trait_impl)]
+/// use hipc::{command, util::assert_bytes_eq, CommandType};
+///
+/// const CLOSE: [u8; 8] = command::new_builder(CommandType::Close).build();
+/// const _: () = assert_bytes_eq(&CLOSE, &[0x04, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00]);
+/// ```
+///
+/// At runtime, the panic message names the offending index:
+/// ```should_panic
+/// hipc::util::assert_bytes_eq(&[1, 2, 3], &[1, 2, 4]);
+/// ```
+#[track_caller]
+pub const fn assert_bytes_eq(actual: &[u8], expected: &[u8]) {
+    let mut index = 0;
+    while index < actual.len() && index < expected.len() {
+        if actual[index] != expected[index] {
+            panic_with_value("Bytes differ at index ", index);
+        }
+        index += 1;
+    }
+
+    if actual.len() != expected.len() {
+        panic_with_value("Byte slices differ in length, the shorter one ends at index ", index);
+    }
+}
+
+/// Hashes `bytes` with 64-bit FNV-1a
+///
+/// This is not a cryptographic hash. It is cheap enough to evaluate at compile time, which
+/// makes it useful for tagging the commands of a `const` table so that duplicates can be
+/// detected, or so that tooling can index them.
+///
+/// # Arguments
+/// * `bytes` - The bytes to hash, usually a serialized command
+///
+/// # Example
+/// ```
+/// #![feature(const_trait_impl)]
+/// use hipc::{command, util::fnv1a, CommandType};
+///
+/// assert_eq!(fnv1a(b""), 0xCBF2_9CE4_8422_2325);
+/// assert_eq!(fnv1a(b"a"), 0xAF63_DC4C_8601_EC8C);
+/// assert_eq!(fnv1a(b"foobar"), 0x8594_4171_F739_67E8);
+///
+/// // Tagging a command table and checking it for duplicates at compile time
+/// const TABLE: [&[u8]; 3] = [
+///     &command::new_builder(CommandType::Close).build(),
+///     &command::request_builder().with_raw_data([1u32]).build(),
+///     &command::request_builder().with_raw_data([2u32]).build()
+/// ];
+///
+/// const _: () = {
+///     let mut i = 0;
+///     while i < TABLE.len() {
+///         let mut j = i + 1;
+///         while j < TABLE.len() {
+///             assert!(fnv1a(TABLE[i]) != fnv1a(TABLE[j]), "Duplicate command in table");
+///             j += 1;
+///         }
+///         i += 1;
+///     }
+/// };
+/// ```
+pub const fn fnv1a(bytes: &[u8]) -> u64 {
+    const OFFSET_BASIS: u64 = 0xCBF2_9CE4_8422_2325;
+    const PRIME: u64 = 0x0000_0100_0000_01B3;
+
+    let mut hash = OFFSET_BASIS;
+    let mut index = 0;
+    while index < bytes.len() {
+        hash ^= bytes[index] as u64;
+        hash = hash.wrapping_mul(PRIME);
+        index += 1;
+    }
+
+    hash
+}
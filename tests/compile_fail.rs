@@ -0,0 +1,5 @@
+#[test]
+fn receive_list_conflicts() {
+    let t = trybuild::TestCases::new();
+    t.compile_fail("tests/ui/*.rs");
+}
@@ -0,0 +1,11 @@
+use hipc::{command, packed::{ReceiveListEntry, StaticDescriptor}};
+
+fn main() {
+    // The conflict is reported at `with_inline_buffer`, not at `build`
+    let _ = command::request_builder()
+        .with_recv_static(ReceiveListEntry::new(0x1000, 0x100))
+        .with_send_static(StaticDescriptor::new(0, 0x10, 0x3000))
+        .with_raw_data([1u32, 2])
+        .with_inline_buffer([0u8; 0x10])
+        .build();
+}
@@ -0,0 +1,8 @@
+use hipc::{command, packed::ReceiveListEntry};
+
+fn main() {
+    // The receive list is already used by a pointer buffer
+    let _ = command::request_builder()
+        .with_pointer_buffer(ReceiveListEntry::new(0x1000, 0x100))
+        .with_inline_buffer([0u8; 0x10]);
+}
@@ -0,0 +1,8 @@
+use hipc::{command, packed::ReceiveListEntry};
+
+fn main() {
+    // The receive list is already used by an inline buffer
+    let _ = command::request_builder()
+        .with_inline_buffer([0u8; 0x10])
+        .with_recv_static(ReceiveListEntry::new(0x2000, 0x100));
+}